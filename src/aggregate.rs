@@ -0,0 +1,328 @@
+//! Summaries of search results, following the STAC API
+//! [aggregation extension](https://github.com/stac-api-extensions/aggregation).
+//!
+//! Aggregations are computed with SQL over **pgstac**'s items table, using
+//! **pgstac**'s own translation of the search into a `WHERE` clause, so they
+//! count exactly the items the search would return.
+
+use crate::{Client, Error, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use stac_api::Search;
+use tokio_postgres::{types::ToSql, GenericClient};
+
+/// The most buckets a property frequency can return.
+pub const MAX_BUCKETS: i64 = 10_000;
+
+/// An aggregation to compute over the items matching a search.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Aggregation {
+    /// The number of matching items.
+    TotalCount,
+
+    /// The number of items in each datetime interval.
+    DatetimeFrequency(Interval),
+
+    /// The number of items with each value of a property, most frequent first.
+    ///
+    /// `collection` counts items per collection. Items without the property
+    /// are counted under a `null` key.
+    PropertyFrequency {
+        /// The property's name.
+        property: String,
+
+        /// The most buckets to return, from 1 to [MAX_BUCKETS].
+        limit: i64,
+    },
+
+    /// The number of items whose geometry's centroid is in each geohash cell.
+    GeohashFrequency {
+        /// The geohash length, from 1 to 12.
+        precision: i32,
+    },
+
+    /// The number of items whose geometry's centroid is in each cell of a
+    /// regular longitude/latitude grid.
+    ///
+    /// Keys are the `x,y` of each cell's lower-left corner.
+    GridFrequency {
+        /// The width and height of a cell, in degrees, greater than zero and
+        /// at most 360.
+        size: f64,
+    },
+}
+
+/// The width of a datetime histogram's buckets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interval {
+    /// One year.
+    Year,
+
+    /// One month.
+    Month,
+
+    /// One day.
+    Day,
+
+    /// One hour.
+    Hour,
+}
+
+/// The result of an [Aggregation], as it's returned by a STAC API.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AggregationResult {
+    /// The aggregation's name, e.g. `total_count` or `platform_frequency`.
+    pub name: String,
+
+    /// `integer` for counts, `frequency_distribution` for buckets.
+    pub data_type: String,
+
+    /// The value, for aggregations that aren't bucketed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+
+    /// The buckets, for aggregations that are.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub buckets: Option<Vec<Bucket>>,
+}
+
+/// One bucket of a frequency distribution.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Bucket {
+    /// The bucket's key, e.g. a datetime, property value, or geohash.
+    pub key: Option<String>,
+
+    /// Always `frequency_distribution`.
+    pub data_type: String,
+
+    /// The number of items in the bucket.
+    pub frequency: i64,
+}
+
+impl Aggregation {
+    /// Returns this aggregation's name in the aggregation extension.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pgstac::aggregate::Aggregation;
+    ///
+    /// let aggregation = Aggregation::PropertyFrequency {
+    ///     property: "platform".to_string(),
+    ///     limit: 10,
+    /// };
+    /// assert_eq!(aggregation.name(), "platform_frequency");
+    /// ```
+    pub fn name(&self) -> String {
+        match self {
+            Aggregation::TotalCount => "total_count".to_string(),
+            Aggregation::DatetimeFrequency(_) => "datetime_frequency".to_string(),
+            Aggregation::PropertyFrequency { property, .. } => format!("{}_frequency", property),
+            Aggregation::GeohashFrequency { .. } => "geohash_frequency".to_string(),
+            Aggregation::GridFrequency { .. } => "grid_frequency".to_string(),
+        }
+    }
+
+    /// Returns [Error::InvalidParameter] if a limit, precision, or grid size
+    /// is out of range.
+    fn validate(&self) -> Result<()> {
+        let (name, reason) = match self {
+            Aggregation::PropertyFrequency { limit, .. } if !(1..=MAX_BUCKETS).contains(limit) => (
+                "limit",
+                format!("{} isn't between 1 and {}", limit, MAX_BUCKETS),
+            ),
+            Aggregation::GeohashFrequency { precision } if !(1..=12).contains(precision) => {
+                ("precision", format!("{} isn't between 1 and 12", precision))
+            }
+            Aggregation::GridFrequency { size } if size.is_nan() || *size <= 0. || *size > 360. => {
+                (
+                    "size",
+                    format!("{} isn't greater than zero and at most 360", size),
+                )
+            }
+            _ => return Ok(()),
+        };
+        Err(Error::InvalidParameter {
+            name: name.to_string(),
+            reason,
+        })
+    }
+
+    /// Returns the query and its parameters, given the search's `WHERE` clause.
+    fn query(&self, filter: &str) -> (String, Vec<Box<dyn ToSql + Sync>>) {
+        match self {
+            Aggregation::TotalCount => (
+                format!("SELECT NULL::text, count(*) FROM pgstac.items WHERE {}", filter),
+                Vec::new(),
+            ),
+            Aggregation::DatetimeFrequency(interval) => (
+                format!(
+                    "SELECT to_char(date_trunc($1, datetime AT TIME ZONE 'UTC'), 'YYYY-MM-DD\"T\"HH24:MI:SS\"Z\"'), count(*) FROM pgstac.items WHERE {} GROUP BY 1 ORDER BY 1",
+                    filter
+                ),
+                vec![Box::new(interval.as_str())],
+            ),
+            Aggregation::PropertyFrequency { property, limit } if property == "collection" => (
+                format!(
+                    "SELECT collection, count(*) FROM pgstac.items WHERE {} GROUP BY 1 ORDER BY 2 DESC, 1 LIMIT $1",
+                    filter
+                ),
+                vec![Box::new(*limit)],
+            ),
+            Aggregation::PropertyFrequency { property, limit } => (
+                format!(
+                    "SELECT content->'properties'->>$1, count(*) FROM pgstac.items WHERE {} GROUP BY 1 ORDER BY 2 DESC, 1 LIMIT $2",
+                    filter
+                ),
+                vec![Box::new(property.clone()), Box::new(*limit)],
+            ),
+            Aggregation::GeohashFrequency { precision } => (
+                format!(
+                    "SELECT ST_GeoHash(ST_Centroid(geometry), $1), count(*) FROM pgstac.items WHERE {} GROUP BY 1 ORDER BY 1",
+                    filter
+                ),
+                vec![Box::new(*precision)],
+            ),
+            Aggregation::GridFrequency { size } => (
+                format!(
+                    "SELECT concat_ws(',', floor(ST_X(c) / $1) * $1, floor(ST_Y(c) / $1) * $1), count(*) FROM (SELECT ST_Centroid(geometry) AS c FROM pgstac.items WHERE {}) AS centroids GROUP BY 1 ORDER BY 1",
+                    filter
+                ),
+                vec![Box::new(*size)],
+            ),
+        }
+    }
+}
+
+impl Interval {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Interval::Year => "year",
+            Interval::Month => "month",
+            Interval::Day => "day",
+            Interval::Hour => "hour",
+        }
+    }
+}
+
+pub(crate) async fn aggregate<C: GenericClient>(
+    client: &Client<'_, C>,
+    search: Search,
+    aggregations: &[Aggregation],
+) -> Result<Vec<AggregationResult>> {
+    for aggregation in aggregations {
+        aggregation.validate()?;
+    }
+    let search = serde_json::to_value(search)?;
+    let filter: String = client
+        .query_one("SELECT pgstac.stac_search_to_where($1::jsonb)", &[&search])
+        .await?
+        .try_get(0)?;
+    let mut results = Vec::with_capacity(aggregations.len());
+    for aggregation in aggregations {
+        let (query, params) = aggregation.query(&filter);
+        let params = params
+            .iter()
+            .map(|param| param.as_ref() as &(dyn ToSql + Sync))
+            .collect::<Vec<_>>();
+        let rows = client.query(&query, &params).await?;
+        let result = if *aggregation == Aggregation::TotalCount {
+            let count: i64 = rows
+                .first()
+                .map(|row| row.try_get(1))
+                .transpose()?
+                .unwrap_or(0);
+            AggregationResult {
+                name: aggregation.name(),
+                data_type: "integer".to_string(),
+                value: Some(count.into()),
+                buckets: None,
+            }
+        } else {
+            let buckets = rows
+                .into_iter()
+                .map(|row| {
+                    Ok(Bucket {
+                        key: row.try_get(0)?,
+                        data_type: "frequency_distribution".to_string(),
+                        frequency: row.try_get(1)?,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            AggregationResult {
+                name: aggregation.name(),
+                data_type: "frequency_distribution".to_string(),
+                value: None,
+                buckets: Some(buckets),
+            }
+        };
+        results.push(result);
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Aggregation, Interval, MAX_BUCKETS};
+    use crate::Error;
+
+    #[test]
+    fn names() {
+        assert_eq!(Aggregation::TotalCount.name(), "total_count");
+        assert_eq!(
+            Aggregation::DatetimeFrequency(Interval::Month).name(),
+            "datetime_frequency"
+        );
+        assert_eq!(
+            Aggregation::GeohashFrequency { precision: 1 }.name(),
+            "geohash_frequency"
+        );
+        assert_eq!(
+            Aggregation::GridFrequency { size: 1. }.name(),
+            "grid_frequency"
+        );
+    }
+
+    #[test]
+    fn validate() {
+        for aggregation in [
+            Aggregation::PropertyFrequency {
+                property: "platform".to_string(),
+                limit: 0,
+            },
+            Aggregation::PropertyFrequency {
+                property: "platform".to_string(),
+                limit: -1,
+            },
+            Aggregation::PropertyFrequency {
+                property: "platform".to_string(),
+                limit: MAX_BUCKETS + 1,
+            },
+            Aggregation::GeohashFrequency { precision: 0 },
+            Aggregation::GeohashFrequency { precision: 13 },
+            Aggregation::GridFrequency { size: 0. },
+            Aggregation::GridFrequency { size: -1. },
+            Aggregation::GridFrequency { size: 361. },
+            Aggregation::GridFrequency { size: f64::NAN },
+            Aggregation::GridFrequency {
+                size: f64::INFINITY,
+            },
+        ] {
+            assert!(matches!(
+                aggregation.validate().unwrap_err(),
+                Error::InvalidParameter { .. }
+            ));
+        }
+        for aggregation in [
+            Aggregation::TotalCount,
+            Aggregation::PropertyFrequency {
+                property: "platform".to_string(),
+                limit: MAX_BUCKETS,
+            },
+            Aggregation::GeohashFrequency { precision: 12 },
+            Aggregation::GridFrequency { size: 0.5 },
+        ] {
+            aggregation.validate().unwrap();
+        }
+    }
+}
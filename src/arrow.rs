@@ -0,0 +1,490 @@
+//! Converting items to [Arrow](https://arrow.apache.org/) record batches.
+//!
+//! Items are laid out with the
+//! [stac-geoparquet](https://github.com/stac-utils/stac-geoparquet/blob/main/spec/stac-geoparquet-spec.md)
+//! column mapping: properties are lifted to top-level columns, `geometry` is
+//! WKB, `bbox` is a struct, and datetimes are UTC timestamps. Everything else
+//! is inferred from the JSON. A property with the same name as a top-level
+//! item field, like `id` or `collection`, is lifted to a column with a
+//! `properties.` prefix instead.
+
+use crate::{Error, Result};
+use arrow_array::{ArrayRef, BinaryArray, RecordBatch};
+use arrow_json::reader::{infer_json_schema_from_iterator, ReaderBuilder};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use geojson::Geometry;
+use serde_json::{Map, Value};
+use std::sync::Arc;
+
+/// The name of the geometry column.
+pub const GEOMETRY_COLUMN: &str = "geometry";
+
+/// Top-level item fields, which properties can't replace.
+const ITEM_FIELDS: [&str; 9] = [
+    "type",
+    "stac_version",
+    "stac_extensions",
+    "id",
+    GEOMETRY_COLUMN,
+    "bbox",
+    "links",
+    "assets",
+    "collection",
+];
+
+const DATETIME_COLUMNS: [&str; 5] = [
+    "datetime",
+    "start_datetime",
+    "end_datetime",
+    "created",
+    "updated",
+];
+
+/// Where item properties go in a record batch.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Layout {
+    /// Properties are lifted to top-level columns, as in stac-geoparquet.
+    #[default]
+    Flat,
+
+    /// Properties are kept in a `properties` struct column.
+    Struct,
+}
+
+/// Converts items to a record batch with the [Layout::Flat] layout.
+///
+/// The schema is inferred from the items, so items whose fields have
+/// conflicting types are an error.
+///
+/// # Examples
+///
+/// ```
+/// use serde_json::json;
+///
+/// let item = json!({
+///     "type": "Feature",
+///     "id": "an-id",
+///     "geometry": {"type": "Point", "coordinates": [-105.1019, 40.1672]},
+///     "bbox": [-105.1019, 40.1672, -105.1019, 40.1672],
+///     "properties": {"datetime": "2023-01-08T00:00:00Z"},
+///     "links": [],
+///     "assets": {}
+/// });
+/// let batch = pgstac::arrow::record_batch(&[item.as_object().unwrap().clone()]).unwrap();
+/// assert_eq!(batch.num_rows(), 1);
+/// ```
+pub fn record_batch(items: &[Map<String, Value>]) -> Result<RecordBatch> {
+    record_batch_with_layout(items, Layout::Flat)
+}
+
+/// Converts items to a record batch, laying out properties as requested.
+///
+/// With [Layout::Struct], datetimes inside `properties` are left as strings.
+pub fn record_batch_with_layout(
+    items: &[Map<String, Value>],
+    layout: Layout,
+) -> Result<RecordBatch> {
+    let (rows, geometries) = rows(items, layout)?;
+    let inferred = infer_json_schema_from_iterator(rows.iter().map(Ok))?;
+    let mut fields = inferred
+        .fields()
+        .iter()
+        .map(|field| {
+            if DATETIME_COLUMNS.contains(&field.name().as_str())
+                && field.data_type() == &DataType::Utf8
+            {
+                Arc::new(Field::new(
+                    field.name(),
+                    DataType::Timestamp(TimeUnit::Millisecond, Some("+00:00".into())),
+                    true,
+                ))
+            } else {
+                field.clone()
+            }
+        })
+        .collect::<Vec<_>>();
+    fields.push(Arc::new(Field::new(
+        GEOMETRY_COLUMN,
+        DataType::Binary,
+        true,
+    )));
+    decode(rows, geometries, Arc::new(Schema::new(fields)))
+}
+
+/// Converts items to a record batch with an existing schema, e.g. one from
+/// an earlier batch.
+///
+/// Fields that aren't in the schema are dropped and missing ones are null.
+/// A field whose type doesn't match the schema is an error.
+///
+/// # Examples
+///
+/// ```
+/// use pgstac::arrow::Layout;
+/// use serde_json::json;
+///
+/// let item = json!({"type": "Feature", "id": "a", "geometry": null, "properties": {"foo": 42}});
+/// let items = [item.as_object().unwrap().clone()];
+/// let batch = pgstac::arrow::record_batch_with_layout(&items, Layout::Flat).unwrap();
+/// let item = json!({"type": "Feature", "id": "b", "geometry": null, "properties": {}});
+/// let items = [item.as_object().unwrap().clone()];
+/// let next = pgstac::arrow::record_batch_with_schema(&items, Layout::Flat, batch.schema()).unwrap();
+/// assert_eq!(next.schema(), batch.schema());
+/// ```
+pub fn record_batch_with_schema(
+    items: &[Map<String, Value>],
+    layout: Layout,
+    schema: SchemaRef,
+) -> Result<RecordBatch> {
+    let (rows, geometries) = rows(items, layout)?;
+    decode(rows, geometries, schema)
+}
+
+/// Lays out items as JSON rows, with their geometries as WKB.
+fn rows(
+    items: &[Map<String, Value>],
+    layout: Layout,
+) -> Result<(Vec<Value>, Vec<Option<Vec<u8>>>)> {
+    let mut rows = Vec::with_capacity(items.len());
+    let mut geometries = Vec::with_capacity(items.len());
+    for item in items {
+        let mut row = item.clone();
+        geometries.push(match row.remove(GEOMETRY_COLUMN) {
+            None | Some(Value::Null) => None,
+            Some(value) => Some(wkb(&serde_json::from_value(value)?)),
+        });
+        if layout == Layout::Flat {
+            if let Some(Value::Object(properties)) = row.remove("properties") {
+                for (key, value) in properties {
+                    let key = if ITEM_FIELDS.contains(&key.as_str()) {
+                        format!("properties.{}", key)
+                    } else {
+                        key
+                    };
+                    let _ = row.insert(key, value);
+                }
+            }
+        }
+        if let Some(Value::Array(bbox)) = row.remove("bbox") {
+            let _ = row.insert("bbox".to_string(), bbox_struct(bbox));
+        }
+        rows.push(Value::Object(row));
+    }
+    Ok((rows, geometries))
+}
+
+/// Decodes rows into a record batch with `schema`, filling in its geometry column.
+fn decode(
+    rows: Vec<Value>,
+    geometries: Vec<Option<Vec<u8>>>,
+    schema: SchemaRef,
+) -> Result<RecordBatch> {
+    let json_schema = Arc::new(Schema::new(
+        schema
+            .fields()
+            .iter()
+            .filter(|field| field.name() != GEOMETRY_COLUMN)
+            .cloned()
+            .collect::<Vec<_>>(),
+    ));
+    let mut decoder = ReaderBuilder::new(json_schema.clone())
+        .with_batch_size(rows.len().max(1))
+        .build_decoder()?;
+    decoder.serialize(&rows)?;
+    let batch = decoder
+        .flush()?
+        .unwrap_or_else(|| RecordBatch::new_empty(json_schema));
+    let mut columns = batch.columns().iter();
+    let mut geometries = Some(geometries);
+    let columns = schema
+        .fields()
+        .iter()
+        .map(|field| {
+            if field.name() == GEOMETRY_COLUMN {
+                Arc::new(BinaryArray::from_iter(
+                    geometries.take().unwrap_or_default(),
+                )) as ArrayRef
+            } else {
+                columns
+                    .next()
+                    .cloned()
+                    .expect("the decoded batch has a column for every other field")
+            }
+        })
+        .collect::<Vec<_>>();
+    RecordBatch::try_new(schema, columns).map_err(Error::from)
+}
+
+fn bbox_struct(bbox: Vec<Value>) -> Value {
+    let names: &[&str] = if bbox.len() == 6 {
+        &["xmin", "ymin", "zmin", "xmax", "ymax", "zmax"]
+    } else {
+        &["xmin", "ymin", "xmax", "ymax"]
+    };
+    Value::Object(
+        names
+            .iter()
+            .map(|name| name.to_string())
+            .zip(bbox)
+            .collect(),
+    )
+}
+
+/// Encodes a geometry as little-endian ISO WKB.
+fn wkb(geometry: &Geometry) -> Vec<u8> {
+    let mut buf = Vec::new();
+    let has_z = first_position(&geometry.value).is_some_and(|position| position.len() > 2);
+    write_geometry(&mut buf, &geometry.value, has_z);
+    buf
+}
+
+fn first_position(value: &geojson::Value) -> Option<&Vec<f64>> {
+    use geojson::Value::*;
+    match value {
+        Point(position) => Some(position),
+        MultiPoint(positions) | LineString(positions) => positions.first(),
+        MultiLineString(lines) | Polygon(lines) => lines.first().and_then(|line| line.first()),
+        MultiPolygon(polygons) => polygons
+            .first()
+            .and_then(|polygon| polygon.first())
+            .and_then(|ring| ring.first()),
+        GeometryCollection(geometries) => geometries
+            .iter()
+            .find_map(|geometry| first_position(&geometry.value)),
+    }
+}
+
+fn write_geometry(buf: &mut Vec<u8>, value: &geojson::Value, has_z: bool) {
+    use geojson::Value::*;
+    let code: u32 = match value {
+        Point(_) => 1,
+        LineString(_) => 2,
+        Polygon(_) => 3,
+        MultiPoint(_) => 4,
+        MultiLineString(_) => 5,
+        MultiPolygon(_) => 6,
+        GeometryCollection(_) => 7,
+    };
+    buf.push(1);
+    buf.extend((code + if has_z { 1000 } else { 0 }).to_le_bytes());
+    match value {
+        Point(position) => write_position(buf, position, has_z),
+        LineString(positions) => write_positions(buf, positions, has_z),
+        Polygon(rings) => write_rings(buf, rings, has_z),
+        MultiPoint(positions) => {
+            write_len(buf, positions.len());
+            for position in positions {
+                write_geometry(buf, &Point(position.clone()), has_z);
+            }
+        }
+        MultiLineString(lines) => {
+            write_len(buf, lines.len());
+            for line in lines {
+                write_geometry(buf, &LineString(line.clone()), has_z);
+            }
+        }
+        MultiPolygon(polygons) => {
+            write_len(buf, polygons.len());
+            for polygon in polygons {
+                write_geometry(buf, &Polygon(polygon.clone()), has_z);
+            }
+        }
+        GeometryCollection(geometries) => {
+            write_len(buf, geometries.len());
+            for geometry in geometries {
+                write_geometry(buf, &geometry.value, has_z);
+            }
+        }
+    }
+}
+
+fn write_rings(buf: &mut Vec<u8>, rings: &[Vec<Vec<f64>>], has_z: bool) {
+    write_len(buf, rings.len());
+    for ring in rings {
+        write_positions(buf, ring, has_z);
+    }
+}
+
+fn write_positions(buf: &mut Vec<u8>, positions: &[Vec<f64>], has_z: bool) {
+    write_len(buf, positions.len());
+    for position in positions {
+        write_position(buf, position, has_z);
+    }
+}
+
+fn write_position(buf: &mut Vec<u8>, position: &[f64], has_z: bool) {
+    let dimensions = if has_z { 3 } else { 2 };
+    for i in 0..dimensions {
+        buf.extend(position.get(i).copied().unwrap_or_default().to_le_bytes());
+    }
+}
+
+fn write_len(buf: &mut Vec<u8>, len: usize) {
+    buf.extend((len as u32).to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::{Array, BinaryArray, TimestampMillisecondArray};
+    use arrow_schema::DataType;
+    use geojson::Geometry;
+    use serde_json::json;
+
+    #[test]
+    fn record_batch() {
+        let items = [
+            json!({
+                "type": "Feature",
+                "id": "a",
+                "geometry": {"type": "Point", "coordinates": [1.0, 2.0]},
+                "bbox": [1.0, 2.0, 1.0, 2.0],
+                "properties": {"datetime": "2023-01-08T00:00:00Z", "foo": 42},
+                "links": [],
+                "assets": {}
+            }),
+            json!({
+                "type": "Feature",
+                "id": "b",
+                "geometry": null,
+                "properties": {"datetime": "2023-01-09T00:00:00Z"},
+                "links": [],
+                "assets": {}
+            }),
+        ]
+        .map(|item| item.as_object().unwrap().clone());
+        let batch = super::record_batch(&items).unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        let schema = batch.schema();
+        assert!(schema.field_with_name("properties").is_err());
+        assert_eq!(
+            schema.field_with_name("foo").unwrap().data_type(),
+            &DataType::Int64
+        );
+        assert!(matches!(
+            schema.field_with_name("bbox").unwrap().data_type(),
+            DataType::Struct(fields) if fields.len() == 4
+        ));
+        let datetime = batch
+            .column_by_name("datetime")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<TimestampMillisecondArray>()
+            .unwrap();
+        assert_eq!(datetime.value(0), 1673136000000);
+        let geometry = batch
+            .column_by_name("geometry")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<BinaryArray>()
+            .unwrap();
+        assert!(geometry.is_null(1));
+        let mut expected = vec![1, 1, 0, 0, 0];
+        expected.extend(1.0f64.to_le_bytes());
+        expected.extend(2.0f64.to_le_bytes());
+        assert_eq!(geometry.value(0), expected);
+    }
+
+    #[test]
+    fn colliding_properties() {
+        let item = json!({
+            "type": "Feature",
+            "id": "a",
+            "geometry": null,
+            "collection": "collection-id",
+            "properties": {"id": 42, "collection": "other", "geometry": "a string"},
+        });
+        let batch = super::record_batch(&[item.as_object().unwrap().clone()]).unwrap();
+        let schema = batch.schema();
+        assert_eq!(
+            schema.field_with_name("id").unwrap().data_type(),
+            &DataType::Utf8
+        );
+        assert_eq!(
+            schema.field_with_name("properties.id").unwrap().data_type(),
+            &DataType::Int64
+        );
+        assert!(schema.field_with_name("properties.collection").is_ok());
+        assert!(schema.field_with_name("properties.geometry").is_ok());
+        assert_eq!(
+            schema
+                .fields()
+                .iter()
+                .filter(|field| field.name() == "geometry")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn struct_layout() {
+        let item = json!({
+            "type": "Feature",
+            "id": "a",
+            "geometry": null,
+            "properties": {"datetime": "2023-01-08T00:00:00Z", "foo": 42},
+            "links": [],
+            "assets": {}
+        });
+        let batch = super::record_batch_with_layout(
+            &[item.as_object().unwrap().clone()],
+            super::Layout::Struct,
+        )
+        .unwrap();
+        let schema = batch.schema();
+        assert!(schema.field_with_name("foo").is_err());
+        assert!(matches!(
+            schema.field_with_name("properties").unwrap().data_type(),
+            DataType::Struct(fields) if fields.len() == 2
+        ));
+    }
+
+    #[test]
+    fn record_batch_with_schema() {
+        let items = [json!({
+            "type": "Feature",
+            "id": "a",
+            "geometry": {"type": "Point", "coordinates": [1.0, 2.0]},
+            "properties": {"datetime": "2023-01-08T00:00:00Z", "foo": 42},
+        })]
+        .map(|item| item.as_object().unwrap().clone());
+        let batch = super::record_batch(&items).unwrap();
+        let items = [json!({
+            "type": "Feature",
+            "id": "b",
+            "geometry": null,
+            "properties": {"datetime": "2023-01-09T00:00:00Z", "bar": true},
+        })]
+        .map(|item| item.as_object().unwrap().clone());
+        let next =
+            super::record_batch_with_schema(&items, super::Layout::Flat, batch.schema()).unwrap();
+        assert_eq!(next.schema(), batch.schema());
+        assert!(next.column_by_name("foo").unwrap().is_null(0));
+        assert!(next.column_by_name("geometry").unwrap().is_null(0));
+
+        let items = [json!({
+            "type": "Feature",
+            "id": "c",
+            "geometry": null,
+            "properties": {"datetime": "2023-01-09T00:00:00Z", "foo": {"not": "a number"}},
+        })]
+        .map(|item| item.as_object().unwrap().clone());
+        assert!(
+            super::record_batch_with_schema(&items, super::Layout::Flat, batch.schema()).is_err()
+        );
+    }
+
+    #[test]
+    fn wkb() {
+        let geometry: Geometry = serde_json::from_value(json!({
+            "type": "Polygon",
+            "coordinates": [[[0, 0, 1], [1, 0, 1], [1, 1, 1], [0, 0, 1]]]
+        }))
+        .unwrap();
+        let wkb = super::wkb(&geometry);
+        assert_eq!(wkb[0], 1);
+        assert_eq!(u32::from_le_bytes(wkb[1..5].try_into().unwrap()), 1003);
+        assert_eq!(u32::from_le_bytes(wkb[5..9].try_into().unwrap()), 1);
+        assert_eq!(u32::from_le_bytes(wkb[9..13].try_into().unwrap()), 4);
+        assert_eq!(wkb.len(), 13 + 4 * 3 * 8);
+    }
+}
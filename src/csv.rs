@@ -0,0 +1,147 @@
+//! Writing items as CSV.
+//!
+//! Each row has the item's `id`, `collection`, `datetime`, and `bbox`,
+//! followed by any properties that were asked for. Values that aren't
+//! strings or numbers are written as JSON.
+
+use crate::Result;
+use serde_json::{Map, Value};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+const COLUMNS: [&str; 4] = ["id", "collection", "datetime", "bbox"];
+
+/// Writes items as CSV rows, starting with a header.
+#[derive(Debug)]
+pub struct CsvWriter<W> {
+    writer: W,
+    properties: Vec<String>,
+    header: bool,
+}
+
+impl<W: AsyncWrite + Unpin> CsvWriter<W> {
+    /// Creates a new CSV writer that includes these properties as columns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pgstac::csv::CsvWriter;
+    /// use serde_json::json;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let mut writer = CsvWriter::new(Vec::new(), ["eo:cloud_cover"]);
+    /// let item = json!({"id": "an-id", "properties": {"eo:cloud_cover": 42}});
+    /// writer.write(&[item.as_object().unwrap().clone()]).await.unwrap();
+    /// let csv = String::from_utf8(writer.into_inner()).unwrap();
+    /// assert_eq!(csv, "id,collection,datetime,bbox,eo:cloud_cover\nan-id,,,,42\n");
+    /// # })
+    /// ```
+    pub fn new(writer: W, properties: impl IntoIterator<Item = impl ToString>) -> CsvWriter<W> {
+        CsvWriter {
+            writer,
+            properties: properties
+                .into_iter()
+                .map(|property| property.to_string())
+                .collect(),
+            header: false,
+        }
+    }
+
+    /// Writes items, and the header if it hasn't been written yet, then flushes.
+    pub async fn write(&mut self, items: &[Map<String, Value>]) -> Result<()> {
+        let mut buf = String::new();
+        if !self.header {
+            let header = COLUMNS
+                .iter()
+                .copied()
+                .chain(self.properties.iter().map(String::as_str))
+                .map(escape)
+                .collect::<Vec<_>>();
+            push_row(&mut buf, header);
+            self.header = true;
+        }
+        for item in items {
+            let properties = item.get("properties");
+            let row = [
+                item.get("id"),
+                item.get("collection"),
+                properties.and_then(|properties| properties.get("datetime")),
+                item.get("bbox"),
+            ]
+            .into_iter()
+            .chain(
+                self.properties
+                    .iter()
+                    .map(|name| properties.and_then(|properties| properties.get(name))),
+            )
+            .map(cell)
+            .collect();
+            push_row(&mut buf, row);
+        }
+        self.writer.write_all(buf.as_bytes()).await?;
+        self.writer.flush().await?;
+        Ok(())
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+fn push_row(buf: &mut String, row: Vec<String>) {
+    buf.push_str(&row.join(","));
+    buf.push('\n');
+}
+
+fn cell(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => escape(s),
+        Some(Value::Array(array)) if array.iter().all(Value::is_number) => escape(
+            &array
+                .iter()
+                .map(Value::to_string)
+                .collect::<Vec<_>>()
+                .join(","),
+        ),
+        Some(value) => escape(&value.to_string()),
+    }
+}
+
+fn escape(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CsvWriter;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn write() {
+        let mut writer = CsvWriter::new(Vec::new(), ["title", "tags"]);
+        let items = [
+            json!({
+                "id": "a",
+                "collection": "c",
+                "bbox": [1.0, 2.0, 3.0, 4.0],
+                "properties": {"datetime": "2023-01-08T00:00:00Z", "title": "say \"hi\"", "tags": ["x", "y"]}
+            }),
+            json!({"id": "b", "properties": {}}),
+        ]
+        .map(|item| item.as_object().unwrap().clone());
+        writer.write(&items[..1]).await.unwrap();
+        writer.write(&items[1..]).await.unwrap();
+        let csv = String::from_utf8(writer.into_inner()).unwrap();
+        assert_eq!(
+            csv,
+            "id,collection,datetime,bbox,title,tags\n\
+             a,c,2023-01-08T00:00:00Z,\"1.0,2.0,3.0,4.0\",\"say \"\"hi\"\"\",\"[\"\"x\"\",\"\"y\"\"]\"\n\
+             b,,,,,\n"
+        );
+    }
+}
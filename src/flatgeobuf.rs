@@ -0,0 +1,148 @@
+//! Writing items to [FlatGeobuf](https://flatgeobuf.org/).
+
+use crate::Result;
+use ::flatgeobuf::{FgbCrs, FgbWriter, FgbWriterOptions, GeometryType};
+use geozero::geojson::GeoJson;
+use serde_json::{json, Map, Value};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// Collects items into a FlatGeobuf file.
+///
+/// FlatGeobuf files have a header and spatial index that depend on every
+/// feature, so items are buffered until [FlatGeobufWriter::finish]. Items can
+/// have mixed geometry types, and coordinates are WGS 84.
+/// Properties become columns, with the item's `id` and `collection` first.
+pub struct FlatGeobufWriter {
+    writer: FgbWriter<'static>,
+    count: u64,
+}
+
+impl FlatGeobufWriter {
+    /// Creates a new writer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pgstac::flatgeobuf::FlatGeobufWriter;
+    /// use serde_json::json;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let mut writer = FlatGeobufWriter::new().unwrap();
+    /// let item = json!({
+    ///     "id": "an-id",
+    ///     "geometry": {"type": "Point", "coordinates": [-105.1019, 40.1672]},
+    ///     "properties": {"datetime": "2023-01-08T00:00:00Z"}
+    /// });
+    /// writer.add(&[item.as_object().unwrap().clone()]).unwrap();
+    /// let mut buf = Vec::new();
+    /// assert_eq!(writer.finish(&mut buf).await.unwrap(), 1);
+    /// # })
+    /// ```
+    pub fn new() -> Result<FlatGeobufWriter> {
+        Ok(FlatGeobufWriter {
+            writer: FgbWriter::create_with_options(
+                "items",
+                GeometryType::Unknown,
+                FgbWriterOptions {
+                    detect_type: false,
+                    promote_to_multi: false,
+                    crs: FgbCrs {
+                        code: 4326,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            )?,
+            count: 0,
+        })
+    }
+
+    /// Adds items.
+    pub fn add(&mut self, items: &[Map<String, Value>]) -> Result<()> {
+        for item in items {
+            let mut properties = Map::new();
+            for key in ["id", "collection"] {
+                if let Some(value) = item.get(key) {
+                    let _ = properties.insert(key.to_string(), value.clone());
+                }
+            }
+            if let Some(Value::Object(item_properties)) = item.get("properties") {
+                properties.extend(item_properties.clone());
+            }
+            let feature = json!({
+                "type": "Feature",
+                "geometry": item.get("geometry").cloned().unwrap_or(Value::Null),
+                "properties": properties,
+            })
+            .to_string();
+            self.writer.add_feature(GeoJson(&feature))?;
+            self.count += 1;
+        }
+        Ok(())
+    }
+
+    /// Writes the file, returning the number of items in it.
+    pub async fn finish<W>(self, mut writer: W) -> Result<u64>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let mut buf = Vec::new();
+        self.writer.write(&mut buf)?;
+        writer.write_all(&buf).await?;
+        writer.flush().await?;
+        Ok(self.count)
+    }
+}
+
+impl std::fmt::Debug for FlatGeobufWriter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FlatGeobufWriter")
+            .field("count", &self.count)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FlatGeobufWriter;
+    use ::flatgeobuf::{FallibleStreamingIterator, FgbReader};
+    use serde_json::json;
+    use std::io::Cursor;
+
+    #[tokio::test]
+    async fn write() {
+        let mut writer = FlatGeobufWriter::new().unwrap();
+        let items = [
+            json!({
+                "id": "a",
+                "collection": "c",
+                "geometry": {"type": "Point", "coordinates": [1.0, 2.0]},
+                "properties": {"datetime": "2023-01-08T00:00:00Z", "foo": 42}
+            }),
+            json!({
+                "id": "b",
+                "collection": "c",
+                "geometry": {"type": "Polygon", "coordinates": [[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 0.0]]]},
+                "properties": {"datetime": "2023-01-09T00:00:00Z", "foo": 43}
+            }),
+            json!({
+                "id": "c",
+                "geometry": null,
+                "properties": {"datetime": "2023-01-10T00:00:00Z", "bar": "baz"}
+            }),
+        ]
+        .map(|item| item.as_object().unwrap().clone());
+        writer.add(&items).unwrap();
+        let mut buf = Vec::new();
+        assert_eq!(writer.finish(&mut buf).await.unwrap(), 3);
+        let mut reader = FgbReader::open(Cursor::new(buf))
+            .unwrap()
+            .select_all()
+            .unwrap();
+        let mut count = 0;
+        while reader.next().unwrap().is_some() {
+            count += 1;
+        }
+        assert_eq!(count, 3);
+    }
+}
@@ -0,0 +1,258 @@
+//! Synthetic items for seeding test databases and benchmarks.
+//!
+//! An [ItemGenerator] makes items with random footprints inside a bounding
+//! box, datetimes inside a range, properties drawn from [Distribution]s, and
+//! a number of assets. Generators with the same [seed](ItemGenerator::seed)
+//! make the same items.
+//!
+//! # Examples
+//!
+//! ```
+//! use pgstac::generate::{Distribution, ItemGenerator};
+//!
+//! let items = ItemGenerator::new("collection-id")
+//!     .bbox([-106.0, 40.0, -105.0, 41.0])
+//!     .property("eo:cloud_cover", Distribution::Uniform(0.0, 100.0))
+//!     .assets(1, 3)
+//!     .seed(42)
+//!     .items(100);
+//! assert_eq!(items.len(), 100);
+//! ```
+
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde_json::Value;
+use stac::{Asset, Geometry, Item};
+
+/// How a generated property's values are chosen.
+#[derive(Clone, Debug)]
+pub enum Distribution {
+    /// The same value every time.
+    Constant(Value),
+
+    /// A float drawn uniformly from `[min, max)`.
+    Uniform(f64, f64),
+
+    /// An integer drawn uniformly from `[min, max]`.
+    Integer(i64, i64),
+
+    /// One of these values, each equally likely.
+    Choice(Vec<Value>),
+}
+
+/// Makes fake items.
+#[derive(Clone, Debug)]
+pub struct ItemGenerator {
+    collection: String,
+    bbox: [f64; 4],
+    footprint: f64,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    interval: Option<Duration>,
+    properties: Vec<(String, Distribution)>,
+    assets: (usize, usize),
+    seed: Option<u64>,
+}
+
+impl ItemGenerator {
+    /// Creates a generator for items in this collection.
+    ///
+    /// By default, items are 0.1° squares anywhere in the world, with a
+    /// datetime in 2023, no extra properties, and one asset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pgstac::generate::ItemGenerator;
+    ///
+    /// let item = ItemGenerator::new("collection-id").items(1).pop().unwrap();
+    /// assert_eq!(item.collection.as_deref(), Some("collection-id"));
+    /// ```
+    pub fn new(collection: impl ToString) -> ItemGenerator {
+        ItemGenerator {
+            collection: collection.to_string(),
+            bbox: [-180.0, -90.0, 180.0, 90.0],
+            footprint: 0.1,
+            start: DateTime::from_timestamp(1_672_531_200, 0).unwrap(),
+            end: DateTime::from_timestamp(1_704_067_200, 0).unwrap(),
+            interval: None,
+            properties: Vec::new(),
+            assets: (1, 1),
+            seed: None,
+        }
+    }
+
+    /// Sets the `[xmin, ymin, xmax, ymax]` that every footprint is inside.
+    pub fn bbox(mut self, bbox: [f64; 4]) -> ItemGenerator {
+        self.bbox = bbox;
+        self
+    }
+
+    /// Sets the largest width and height of a footprint, in degrees.
+    ///
+    /// Zero makes points.
+    pub fn footprint(mut self, footprint: f64) -> ItemGenerator {
+        self.footprint = footprint;
+        self
+    }
+
+    /// Sets the range that datetimes are drawn from.
+    pub fn datetimes(mut self, start: DateTime<Utc>, end: DateTime<Utc>) -> ItemGenerator {
+        self.start = start;
+        self.end = end;
+        self
+    }
+
+    /// Gives items a `start_datetime` and `end_datetime` this far apart,
+    /// instead of a single `datetime`.
+    pub fn interval(mut self, interval: Duration) -> ItemGenerator {
+        self.interval = Some(interval);
+        self
+    }
+
+    /// Adds a property whose values come from a distribution.
+    pub fn property(mut self, name: impl ToString, distribution: Distribution) -> ItemGenerator {
+        self.properties.push((name.to_string(), distribution));
+        self
+    }
+
+    /// Sets the smallest and largest number of assets per item.
+    pub fn assets(mut self, min: usize, max: usize) -> ItemGenerator {
+        self.assets = (min, max.max(min));
+        self
+    }
+
+    /// Seeds the random number generator, so the same items are made every
+    /// time.
+    pub fn seed(mut self, seed: u64) -> ItemGenerator {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Makes `count` items, with ids `item-0`, `item-1`, and so on.
+    pub fn items(&self, count: usize) -> Vec<Item> {
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        (0..count).map(|i| self.item(i, &mut rng)).collect()
+    }
+
+    fn item(&self, i: usize, rng: &mut StdRng) -> Item {
+        let mut item = Item::new(format!("item-{}", i)).collection(&self.collection);
+        let [xmin, ymin, xmax, ymax] = self.bbox;
+        let width = self.footprint.min(xmax - xmin).max(0.0);
+        let height = self.footprint.min(ymax - ymin).max(0.0);
+        let x = uniform(rng, xmin, xmax - width);
+        let y = uniform(rng, ymin, ymax - height);
+        let (geometry, bbox) = if width == 0.0 && height == 0.0 {
+            (Geometry::point(x, y), vec![x, y, x, y])
+        } else {
+            (
+                Geometry::rect(x, y, x + width, y + height),
+                vec![x, y, x + width, y + height],
+            )
+        };
+        item.geometry = Some(geometry);
+        item.bbox = Some(bbox);
+
+        let span = (self.end - self.start).num_seconds().max(0);
+        let start = self.start + Duration::seconds(rng.gen_range(0..=span));
+        if let Some(interval) = self.interval {
+            item.properties.datetime = None;
+            let _ = item
+                .properties
+                .additional_fields
+                .insert("start_datetime".to_string(), rfc3339(start).into());
+            let _ = item
+                .properties
+                .additional_fields
+                .insert("end_datetime".to_string(), rfc3339(start + interval).into());
+        } else {
+            item.properties.datetime = Some(rfc3339(start));
+        }
+
+        for (name, distribution) in &self.properties {
+            let value = match distribution {
+                Distribution::Constant(value) => value.clone(),
+                Distribution::Uniform(min, max) => uniform(rng, *min, *max).into(),
+                Distribution::Integer(min, max) => rng.gen_range(*min..=(*max).max(*min)).into(),
+                Distribution::Choice(values) => values.choose(rng).cloned().unwrap_or(Value::Null),
+            };
+            let _ = item
+                .properties
+                .additional_fields
+                .insert(name.clone(), value);
+        }
+
+        for a in 0..rng.gen_range(self.assets.0..=self.assets.1) {
+            let key = format!("asset-{}", a);
+            let href = format!(
+                "https://example.com/{}/{}/{}.tif",
+                self.collection, item.id, key
+            );
+            let _ = item.assets.insert(key, Asset::new(href));
+        }
+        item
+    }
+}
+
+fn uniform(rng: &mut StdRng, min: f64, max: f64) -> f64 {
+    if max > min {
+        rng.gen_range(min..max)
+    } else {
+        min
+    }
+}
+
+fn rfc3339(datetime: DateTime<Utc>) -> String {
+    datetime.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Distribution, ItemGenerator};
+    use chrono::Duration;
+    use serde_json::json;
+
+    #[test]
+    fn seeded() {
+        let generator = ItemGenerator::new("collection-id")
+            .bbox([-106.0, 40.0, -105.0, 41.0])
+            .property(
+                "platform",
+                Distribution::Choice(vec![json!("a"), json!("b")]),
+            )
+            .property("eo:cloud_cover", Distribution::Uniform(0.0, 100.0))
+            .assets(2, 4)
+            .seed(42);
+        let items = generator.items(10);
+        assert_eq!(items, generator.items(10));
+        for item in items {
+            let bbox = item.bbox.unwrap();
+            assert!(bbox[0] >= -106.0 && bbox[2] <= -105.0);
+            assert!(bbox[1] >= 40.0 && bbox[3] <= 41.0);
+            assert!((2..=4).contains(&item.assets.len()));
+            let cloud_cover = item.properties.additional_fields["eo:cloud_cover"]
+                .as_f64()
+                .unwrap();
+            assert!((0.0..100.0).contains(&cloud_cover));
+        }
+    }
+
+    #[test]
+    fn interval() {
+        let item = ItemGenerator::new("collection-id")
+            .footprint(0.0)
+            .interval(Duration::days(1))
+            .items(1)
+            .pop()
+            .unwrap();
+        assert!(item.properties.datetime.is_none());
+        assert!(item
+            .properties
+            .additional_fields
+            .contains_key("end_datetime"));
+        assert_eq!(item.geometry.unwrap().r#type, "Point");
+    }
+}
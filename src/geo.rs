@@ -0,0 +1,91 @@
+//! Searching with [geo-types](https://docs.rs/geo-types) geometries and WKT.
+//!
+//! A search's `intersects` is GeoJSON. [IntersectsExt] sets it from a
+//! [geo_types::Geometry], which is also what the [geo](https://docs.rs/geo)
+//! crate uses, or from well-known text.
+//!
+//! # Examples
+//!
+//! ```
+//! use pgstac::geo::IntersectsExt;
+//! use stac_api::Search;
+//!
+//! let search = Search::default()
+//!     .intersects_wkt("POLYGON((-106 40, -105 40, -105 41, -106 41, -106 40))")
+//!     .unwrap();
+//! assert_eq!(search.intersects.unwrap().r#type, "Polygon");
+//! ```
+
+use crate::{Error, Result};
+use geo_types::Geometry;
+use stac_api::Search;
+use wkt::TryFromWkt;
+
+/// Sets a search's `intersects` from something that isn't GeoJSON.
+pub trait IntersectsExt: Sized {
+    /// Sets `intersects` to a [geo_types::Geometry].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use geo_types::point;
+    /// use pgstac::geo::IntersectsExt;
+    /// use stac_api::Search;
+    ///
+    /// let search = Search::default()
+    ///     .intersects_geometry(point!(x: -105.1, y: 40.1))
+    ///     .unwrap();
+    /// ```
+    fn intersects_geometry(self, geometry: impl Into<Geometry>) -> Result<Self>;
+
+    /// Sets `intersects` to a geometry in well-known text.
+    ///
+    /// Text that isn't valid WKT is an [Error::InvalidParameter].
+    fn intersects_wkt(self, wkt: &str) -> Result<Self>;
+}
+
+impl IntersectsExt for Search {
+    fn intersects_geometry(mut self, geometry: impl Into<Geometry>) -> Result<Search> {
+        crate::geometry::set_intersects(&mut self, &geojson::Geometry::from(&geometry.into()))?;
+        Ok(self)
+    }
+
+    fn intersects_wkt(self, wkt: &str) -> Result<Search> {
+        let geometry = Geometry::try_from_wkt_str(wkt).map_err(|err| Error::InvalidParameter {
+            name: "intersects".to_string(),
+            reason: err.to_string(),
+        })?;
+        self.intersects_geometry(geometry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IntersectsExt;
+    use geo_types::{point, Geometry};
+    use serde_json::json;
+    use stac_api::Search;
+
+    #[test]
+    fn intersects_geometry() {
+        let search = Search::default()
+            .intersects_geometry(point!(x: -105.1, y: 40.1))
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(search.intersects.unwrap()).unwrap(),
+            json!({"type": "Point", "coordinates": [-105.1, 40.1]})
+        );
+        let polygon = Geometry::Rect(geo_types::Rect::new((-106., 40.), (-105., 41.)));
+        let search = Search::default().intersects_geometry(polygon).unwrap();
+        assert_eq!(search.intersects.unwrap().r#type, "Polygon");
+    }
+
+    #[test]
+    fn intersects_wkt() {
+        let search = Search::default()
+            .intersects_wkt("POINT(-105.1 40.1)")
+            .unwrap();
+        assert_eq!(search.intersects.unwrap().r#type, "Point");
+        assert!(Search::default().intersects_wkt("POINT(").is_err());
+    }
+}
@@ -0,0 +1,166 @@
+//! Conversions between geometry types.
+//!
+//! [stac::Item::geometry] is a [stac::Geometry], a search's `intersects` is
+//! the same thing from the version of **stac** that **stac-api** uses, and
+//! most other crates use [geojson::Geometry]. These functions convert between
+//! them without a trip through JSON at every call site, and [bbox] derives a
+//! bounding box from a geometry's coordinates.
+//!
+//! # Examples
+//!
+//! ```
+//! use pgstac::geometry;
+//!
+//! let point = stac::Geometry::point(-105.1, 40.1);
+//! let geojson = geometry::to_geojson(&point).unwrap();
+//! assert_eq!(geometry::from_geojson(&geojson), point);
+//! assert_eq!(geometry::bbox(&geojson).unwrap(), vec![-105.1, 40.1, -105.1, 40.1]);
+//! ```
+
+use crate::{Error, Result};
+use serde_json::Value;
+use stac_api::Search;
+
+/// Converts a [stac::Geometry] into a [geojson::Geometry].
+///
+/// Fails if the geometry isn't valid GeoJSON, e.g. if its coordinates are
+/// missing.
+pub fn to_geojson(geometry: &stac::Geometry) -> Result<geojson::Geometry> {
+    let mut object = geometry.attributes.clone();
+    let _ = object.insert("type".to_string(), geometry.r#type.clone().into());
+    geojson::Geometry::from_json_object(object).map_err(|err| Error::Boxed(Box::new(err)))
+}
+
+/// Converts a [geojson::Geometry] into a [stac::Geometry].
+pub fn from_geojson(geometry: &geojson::Geometry) -> stac::Geometry {
+    let mut attributes = geojson::JsonObject::from(geometry);
+    let r#type = match attributes.remove("type") {
+        Some(Value::String(r#type)) => r#type,
+        _ => geometry.value.type_name().to_string(),
+    };
+    stac::Geometry { r#type, attributes }
+}
+
+/// Returns a search's `intersects` as a [geojson::Geometry], if it has one.
+pub fn intersects(search: &Search) -> Result<Option<geojson::Geometry>> {
+    search
+        .intersects
+        .as_ref()
+        .map(|intersects| {
+            geojson::Geometry::from_json_value(serde_json::to_value(intersects)?)
+                .map_err(|err| Error::Boxed(Box::new(err)))
+        })
+        .transpose()
+}
+
+/// Sets a search's `intersects` to a [geojson::Geometry].
+///
+/// # Examples
+///
+/// ```
+/// use pgstac::geometry;
+/// use stac_api::Search;
+///
+/// let mut search = Search::default();
+/// let point = geojson::Geometry::new(geojson::Value::Point(vec![-105.1, 40.1]));
+/// geometry::set_intersects(&mut search, &point).unwrap();
+/// assert_eq!(geometry::intersects(&search).unwrap().unwrap(), point);
+/// ```
+pub fn set_intersects(search: &mut Search, geometry: &geojson::Geometry) -> Result<()> {
+    search.intersects = Some(serde_json::from_value(serde_json::to_value(geometry)?)?);
+    Ok(())
+}
+
+/// Returns the bounding box of a geometry's coordinates.
+///
+/// The box is `[xmin, ymin, xmax, ymax]`, or `[xmin, ymin, zmin, xmax, ymax,
+/// zmax]` if every position has a z. Geometries without any positions, e.g.
+/// empty collections, have no box. Boxes don't wrap across the antimeridian.
+pub fn bbox(geometry: &geojson::Geometry) -> Option<Vec<f64>> {
+    let mut positions = Vec::new();
+    collect_positions(&geometry.value, &mut positions);
+    let dimensions = positions.iter().map(|position| position.len()).min()?;
+    let dimensions = if dimensions >= 3 {
+        3
+    } else if dimensions == 2 {
+        2
+    } else {
+        return None;
+    };
+    let mut min = vec![f64::INFINITY; dimensions];
+    let mut max = vec![f64::NEG_INFINITY; dimensions];
+    for position in positions {
+        for i in 0..dimensions {
+            min[i] = min[i].min(position[i]);
+            max[i] = max[i].max(position[i]);
+        }
+    }
+    min.extend(max);
+    Some(min)
+}
+
+fn collect_positions<'a>(value: &'a geojson::Value, positions: &mut Vec<&'a Vec<f64>>) {
+    use geojson::Value::*;
+    match value {
+        Point(position) => positions.push(position),
+        MultiPoint(points) | LineString(points) => positions.extend(points),
+        MultiLineString(lines) | Polygon(lines) => positions.extend(lines.iter().flatten()),
+        MultiPolygon(polygons) => positions.extend(polygons.iter().flatten().flatten()),
+        GeometryCollection(geometries) => {
+            for geometry in geometries {
+                collect_positions(&geometry.value, positions);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geojson::{Geometry, Value};
+    use stac_api::Search;
+
+    #[test]
+    fn roundtrip() {
+        let rect = stac::Geometry::rect(-106.0, 40.0, -105.0, 41.0);
+        let geojson = super::to_geojson(&rect).unwrap();
+        assert!(matches!(geojson.value, Value::Polygon(_)));
+        assert_eq!(super::from_geojson(&geojson), rect);
+
+        let invalid = stac::Geometry {
+            r#type: "Point".to_string(),
+            attributes: Default::default(),
+        };
+        assert!(super::to_geojson(&invalid).is_err());
+    }
+
+    #[test]
+    fn intersects() {
+        let mut search = Search::default();
+        assert!(super::intersects(&search).unwrap().is_none());
+        let point = Geometry::new(Value::Point(vec![-105.1, 40.1]));
+        super::set_intersects(&mut search, &point).unwrap();
+        assert_eq!(search.intersects.as_ref().unwrap().r#type, "Point");
+        assert_eq!(super::intersects(&search).unwrap().unwrap(), point);
+    }
+
+    #[test]
+    fn bbox() {
+        let geojson = super::to_geojson(&stac::Geometry::rect(-106.0, 40.0, -105.0, 41.0)).unwrap();
+        assert_eq!(
+            super::bbox(&geojson).unwrap(),
+            vec![-106.0, 40.0, -105.0, 41.0]
+        );
+        let collection = Geometry::new(Value::GeometryCollection(vec![
+            Geometry::new(Value::Point(vec![1.0, 2.0, 3.0])),
+            Geometry::new(Value::LineString(vec![
+                vec![-1.0, 0.0, 1.0],
+                vec![4.0, 5.0, 6.0],
+            ])),
+        ]));
+        assert_eq!(
+            super::bbox(&collection).unwrap(),
+            vec![-1.0, 0.0, 1.0, 4.0, 5.0, 6.0]
+        );
+        assert!(super::bbox(&Geometry::new(Value::GeometryCollection(Vec::new()))).is_none());
+    }
+}
@@ -0,0 +1,100 @@
+//! Writing items to [stac-geoparquet](https://github.com/stac-utils/stac-geoparquet).
+
+use crate::{arrow::GEOMETRY_COLUMN, Result};
+use arrow_schema::DataType;
+use parquet::{arrow::AsyncArrowWriter, format::KeyValue};
+use serde_json::{json, Map, Value};
+use tokio::io::AsyncWrite;
+
+/// Writes items as a stac-geoparquet file.
+///
+/// The items are converted with [crate::arrow::record_batch], and the file's
+/// `geo` metadata names the WKB geometry column. Parquet can't store empty
+/// structs, so columns that are always an empty object, e.g. `assets` when
+/// no item has any, are left out.
+///
+/// # Examples
+///
+/// ```
+/// use serde_json::json;
+///
+/// # tokio_test::block_on(async {
+/// let item = json!({
+///     "type": "Feature",
+///     "id": "an-id",
+///     "geometry": {"type": "Point", "coordinates": [-105.1019, 40.1672]},
+///     "properties": {"datetime": "2023-01-08T00:00:00Z"},
+///     "links": [],
+///     "assets": {}
+/// });
+/// let mut buf = Vec::new();
+/// pgstac::geoparquet::write(&[item.as_object().unwrap().clone()], &mut buf)
+///     .await
+///     .unwrap();
+/// # })
+/// ```
+pub async fn write<W>(items: &[Map<String, Value>], writer: W) -> Result<()>
+where
+    W: AsyncWrite + Unpin + Send,
+{
+    let batch = crate::arrow::record_batch(items)?;
+    let indices = batch
+        .schema()
+        .fields()
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| !matches!(field.data_type(), DataType::Struct(fields) if fields.is_empty()))
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    let batch = batch.project(&indices)?;
+    let mut writer = AsyncArrowWriter::try_new(writer, batch.schema(), None)?;
+    writer.append_key_value_metadata(KeyValue::new("geo".to_string(), geo_metadata()));
+    writer.write(&batch).await?;
+    let _ = writer.close().await?;
+    Ok(())
+}
+
+fn geo_metadata() -> String {
+    json!({
+        "version": "1.0.0",
+        "primary_column": GEOMETRY_COLUMN,
+        "columns": {
+            GEOMETRY_COLUMN: {
+                "encoding": "WKB",
+                "geometry_types": []
+            }
+        }
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn write() {
+        let item = json!({
+            "type": "Feature",
+            "id": "an-id",
+            "geometry": {"type": "Point", "coordinates": [-105.1019, 40.1672]},
+            "properties": {"datetime": "2023-01-08T00:00:00Z"},
+            "links": [],
+            "assets": {}
+        });
+        let mut buf = Vec::new();
+        super::write(&[item.as_object().unwrap().clone()], &mut buf)
+            .await
+            .unwrap();
+        let reader = SerializedFileReader::new(Bytes::from(buf)).unwrap();
+        let metadata = reader.metadata().file_metadata();
+        assert_eq!(metadata.num_rows(), 1);
+        assert!(metadata
+            .key_value_metadata()
+            .unwrap()
+            .iter()
+            .any(|kv| kv.key == "geo"));
+    }
+}
@@ -0,0 +1,229 @@
+//! Hydrating and dehydrating items, the way **pgstac** does.
+//!
+//! **pgstac** stores items "dehydrated", without the values they share with
+//! their collection's base item (see
+//! [Client::collection_base_item](crate::Client::collection_base_item)), and
+//! "hydrates" them by merging the base item back in when they're read. A
+//! search whose `conf` has `"nohydrate": true` skips that merge and returns
+//! dehydrated items, so readers can hydrate them with [hydrate] instead,
+//! moving the work off the database.
+//!
+//! Dehydrating keeps a value that's in an item and not the base item, and
+//! drops one that's the same in both. A value that's in the base item but
+//! not the item becomes [MAGIC_MARKER], so hydrating doesn't bring it back.
+//! Objects are merged key by key and arrays of the same length element by
+//! element. These are **pgstac**'s `strip_jsonb` and `merge_jsonb`, nulls
+//! included: like `jsonb_strip_nulls`, merged objects lose their null
+//! fields, at any depth.
+//!
+//! # Examples
+//!
+//! ```
+//! use pgstac::hydrate;
+//! use serde_json::json;
+//!
+//! let base_item = json!({"type": "Feature", "assets": {"data": {"type": "image/tiff"}}});
+//! let item = json!({"type": "Feature", "id": "an-id", "assets": {"data": {"href": "data.tif", "type": "image/tiff"}}});
+//! let dehydrated = hydrate::dehydrate(&base_item, &item);
+//! assert_eq!(dehydrated, json!({"id": "an-id", "assets": {"data": {"href": "data.tif"}}}));
+//! assert_eq!(hydrate::hydrate(&base_item, &dehydrated), item);
+//! ```
+
+use serde_json::{Map, Value};
+
+/// The value that marks a field as removed from an item, when its base item
+/// has it.
+pub const MAGIC_MARKER: &str = "𒍟※";
+
+/// Merges a base item into a dehydrated item.
+///
+/// This is **pgstac**'s `merge_jsonb(item, base_item)`.
+pub fn hydrate(base_item: &Value, item: &Value) -> Value {
+    merge(Some(item), Some(base_item)).unwrap_or_default()
+}
+
+/// Removes the values an item shares with a base item.
+///
+/// This is **pgstac**'s `strip_jsonb(item, base_item)`. When **pgstac**
+/// stores an item, it also removes `id`, `geometry`, `collection`, and
+/// `type`, which have columns of their own.
+pub fn dehydrate(base_item: &Value, item: &Value) -> Value {
+    strip(Some(item), Some(base_item)).unwrap_or_default()
+}
+
+/// `merge_jsonb`, where `None` is SQL `NULL`.
+fn merge(a: Option<&Value>, b: Option<&Value>) -> Option<Value> {
+    match (a, b) {
+        (Some(Value::String(a)), _) if a == MAGIC_MARKER => None,
+        (None | Some(Value::Null), _) => b.cloned(),
+        (Some(Value::Object(a)), Some(Value::Object(b))) => each_key(a, b, merge),
+        (Some(Value::Array(a)), Some(Value::Array(b))) if a.len() == b.len() => {
+            each_element(a, b, merge)
+        }
+        (Some(a), _) => Some(a.clone()),
+    }
+}
+
+/// `strip_jsonb`, where `None` is SQL `NULL`.
+fn strip(a: Option<&Value>, b: Option<&Value>) -> Option<Value> {
+    match (a, b) {
+        (None | Some(Value::Null), Some(b)) if !b.is_null() => {
+            Some(Value::String(MAGIC_MARKER.to_string()))
+        }
+        (_, None) | (Some(Value::Null), _) | (None, _) => a.cloned(),
+        (Some(a), Some(b)) if a == b => a.is_object().then(|| Value::Object(Map::new())),
+        (Some(Value::Object(a)), Some(Value::Object(b))) => each_key(a, b, strip),
+        (Some(Value::Array(a)), Some(Value::Array(b))) if a.len() == b.len() => {
+            each_element(a, b, strip)
+        }
+        (Some(a), _) => Some(a.clone()),
+    }
+}
+
+/// Combines two objects key by key, over every key in either, like a
+/// `FULL JOIN` of their `jsonb_each` aggregated with `jsonb_object_agg` and
+/// then `jsonb_strip_nulls`.
+fn each_key(
+    a: &Map<String, Value>,
+    b: &Map<String, Value>,
+    f: impl Fn(Option<&Value>, Option<&Value>) -> Option<Value>,
+) -> Option<Value> {
+    let keys = a
+        .keys()
+        .chain(b.keys().filter(|key| !a.contains_key(*key)))
+        .collect::<Vec<_>>();
+    if keys.is_empty() {
+        // jsonb_object_agg of no rows is NULL.
+        return None;
+    }
+    let mut object = Map::new();
+    for key in keys {
+        if let Some(value) = f(a.get(key), b.get(key)) {
+            let _ = object.insert(key.clone(), value);
+        }
+    }
+    let mut object = Value::Object(object);
+    strip_nulls(&mut object);
+    Some(object)
+}
+
+/// Combines two arrays of the same length element by element, like
+/// `jsonb_agg`, which keeps `NULL`s as JSON nulls.
+fn each_element(
+    a: &[Value],
+    b: &[Value],
+    f: impl Fn(Option<&Value>, Option<&Value>) -> Option<Value>,
+) -> Option<Value> {
+    if a.is_empty() {
+        // jsonb_agg of no rows is NULL.
+        return None;
+    }
+    Some(Value::Array(
+        a.iter()
+            .zip(b)
+            .map(|(a, b)| f(Some(a), Some(b)).unwrap_or_default())
+            .collect(),
+    ))
+}
+
+/// Removes null object fields at any depth, like `jsonb_strip_nulls`.
+fn strip_nulls(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            object.retain(|_, value| !value.is_null());
+            object.values_mut().for_each(strip_nulls);
+        }
+        Value::Array(array) => array.iter_mut().for_each(strip_nulls),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MAGIC_MARKER;
+    use crate::Client;
+    use pgstac_test::pgstac_test;
+    use serde_json::{json, Value};
+    use tokio_postgres::Transaction;
+
+    fn cases() -> Vec<(Value, Value)> {
+        vec![
+            (json!({"a": 1, "b": 2}), json!({"a": 1})),
+            (json!({"a": 1}), json!({"a": 1, "b": 2})),
+            (json!({"a": 1}), json!({"a": 1})),
+            (
+                json!({"a": {"b": 1, "c": 2}}),
+                json!({"a": {"b": 1, "c": 3}}),
+            ),
+            (json!({"a": {"b": 1}}), json!({"a": {"b": 1, "c": 2}})),
+            (json!({"a": [1, 2, 3]}), json!({"a": [1, 4, 3]})),
+            (json!({"a": [1, 2]}), json!({"a": [1, 2, 3]})),
+            (
+                json!({"a": [{"b": 1}, {"c": 2}]}),
+                json!({"a": [{"b": 1}, {"c": 3}]}),
+            ),
+            (json!({"a": null, "b": {"c": null}}), json!({"d": 1})),
+            (json!({"a": null}), json!({"a": 1})),
+            (json!({"a": []}), json!({"a": []})),
+            (json!({"a": "x"}), json!({"a": {"b": 1}})),
+            (json!({}), json!({"a": 1})),
+        ]
+    }
+
+    #[test]
+    fn dehydrate() {
+        let base_item = json!({"type": "Feature", "collection": "c", "assets": {"data": {"type": "image/tiff", "roles": ["data"]}}});
+        let item = json!({"type": "Feature", "collection": "c", "id": "an-id", "assets": {"data": {"href": "data.tif", "roles": ["data"]}}});
+        let dehydrated = super::dehydrate(&base_item, &item);
+        assert_eq!(
+            dehydrated,
+            json!({"id": "an-id", "assets": {"data": {"href": "data.tif", "type": MAGIC_MARKER}}})
+        );
+        assert_eq!(super::hydrate(&base_item, &dehydrated), item);
+    }
+
+    #[test]
+    fn roundtrip() {
+        for (item, base_item) in cases() {
+            let dehydrated = super::dehydrate(&base_item, &item);
+            let mut expected = item.clone();
+            super::strip_nulls(&mut expected);
+            assert_eq!(
+                super::hydrate(&base_item, &dehydrated),
+                expected,
+                "{} with base {}",
+                item,
+                base_item
+            );
+        }
+    }
+
+    #[pgstac_test]
+    async fn matches_sql(client: &Client<'_, Transaction<'_>>) {
+        for (item, base_item) in cases() {
+            let row = client
+                .query_one(
+                    "SELECT pgstac.strip_jsonb($1, $2), pgstac.merge_jsonb($1, $2)",
+                    &[&item, &base_item],
+                )
+                .await
+                .unwrap();
+            let stripped: Option<Value> = row.get(0);
+            let merged: Option<Value> = row.get(1);
+            assert_eq!(
+                super::dehydrate(&base_item, &item),
+                stripped.unwrap_or_default(),
+                "strip {} with base {}",
+                item,
+                base_item
+            );
+            assert_eq!(
+                super::hydrate(&base_item, &item),
+                merged.unwrap_or_default(),
+                "merge {} with base {}",
+                item,
+                base_item
+            );
+        }
+    }
+}
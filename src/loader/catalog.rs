@@ -0,0 +1,206 @@
+//! Crawling static catalogs.
+
+use crate::{Error, Result};
+use serde_json::Value;
+use stac::{Collection, Item};
+use std::collections::{HashSet, VecDeque};
+
+/// Everything found by walking a static catalog.
+#[derive(Debug, Default)]
+pub(super) struct Crawl {
+    /// Collections, in the order they were found.
+    pub(super) collections: Vec<Collection>,
+
+    /// Item hrefs and the id of the collection that links to each one, if any.
+    pub(super) items: Vec<(String, Option<String>)>,
+}
+
+/// Walks a catalog's `child` links, collecting collections and item hrefs.
+///
+/// Items aren't read, so the whole catalog never has to be held in memory.
+pub(super) async fn crawl(href: &str) -> Result<Crawl> {
+    let mut crawl = Crawl::default();
+    let mut visited = HashSet::new();
+    let mut queue = VecDeque::from([href.to_string()]);
+    while let Some(href) = queue.pop_front() {
+        if !visited.insert(href.clone()) {
+            continue;
+        }
+        let value = read(&href).await?;
+        let collection = match value.get("type").and_then(Value::as_str) {
+            Some(stac::CATALOG_TYPE) => None,
+            Some(stac::COLLECTION_TYPE) => {
+                let mut collection: Collection = serde_json::from_value(value.clone())?;
+                for asset in collection.assets.values_mut() {
+                    asset.href = resolve(&href, &asset.href);
+                }
+                let id = collection.id.clone();
+                crawl.collections.push(collection);
+                Some(id)
+            }
+            Some(stac::ITEM_TYPE) => {
+                crawl.items.push((href, None));
+                continue;
+            }
+            other => return Err(Error::UnsupportedType(other.map(String::from))),
+        };
+        for link in value
+            .get("links")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let Some(link_href) = link.get("href").and_then(Value::as_str) else {
+                continue;
+            };
+            match link.get("rel").and_then(Value::as_str) {
+                Some("child") => queue.push_back(resolve(&href, link_href)),
+                Some("item") => crawl
+                    .items
+                    .push((resolve(&href, link_href), collection.clone())),
+                _ => {}
+            }
+        }
+    }
+    Ok(crawl)
+}
+
+/// Reads an item, putting it in its parent collection if it doesn't say otherwise.
+///
+/// Relative asset hrefs are resolved against the item's href, since they
+/// don't mean anything once the item is in the database.
+pub(super) async fn read_item(href: &str, collection: Option<&str>) -> Result<Item> {
+    let mut item: Item = serde_json::from_value(read(href).await?)?;
+    if item.collection.is_none() {
+        item.collection = collection.map(String::from);
+    }
+    for asset in item.assets.values_mut() {
+        asset.href = resolve(href, &asset.href);
+    }
+    Ok(item)
+}
+
+async fn read(href: &str) -> Result<Value> {
+    if is_url(href) {
+        read_url(href).await
+    } else {
+        let buf = tokio::fs::read(href).await?;
+        serde_json::from_slice(&buf).map_err(Error::from)
+    }
+}
+
+#[cfg(feature = "harvest")]
+async fn read_url(href: &str) -> Result<Value> {
+    reqwest::get(href)
+        .await?
+        .error_for_status()?
+        .json()
+        .await
+        .map_err(Error::from)
+}
+
+#[cfg(not(feature = "harvest"))]
+async fn read_url(href: &str) -> Result<Value> {
+    Err(Error::UnsupportedHref(href.to_string()))
+}
+
+fn is_url(href: &str) -> bool {
+    href.starts_with("http://") || href.starts_with("https://")
+}
+
+/// Resolves an href against the href of the document that contains it.
+pub(super) fn resolve(base: &str, href: &str) -> String {
+    if href.contains("://") {
+        return href.to_string();
+    }
+    // Keep a url's scheme and host out of the path arithmetic.
+    let (prefix, path) = match base.find("://") {
+        Some(i) => base.split_at(base[i + 3..].find('/').map_or(base.len(), |j| i + 3 + j)),
+        None => ("", base),
+    };
+    let joined = if href.starts_with('/') {
+        href.to_string()
+    } else {
+        format!("{}{}", &path[..path.rfind('/').map_or(0, |i| i + 1)], href)
+    };
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in joined.split('/') {
+        match segment {
+            "." => {}
+            ".." if segments
+                .last()
+                .is_some_and(|last| !last.is_empty() && *last != "..") =>
+            {
+                let _ = segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+    format!("{}{}", prefix, segments.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn resolve() {
+        for (base, href, expected) in [
+            (
+                "data/catalog/catalog.json",
+                "./collection-id/collection.json",
+                "data/catalog/collection-id/collection.json",
+            ),
+            (
+                "data/catalog/a/collection.json",
+                "../b/item.json",
+                "data/catalog/b/item.json",
+            ),
+            ("catalog.json", "./item.json", "item.json"),
+            ("catalog.json", "../item.json", "../item.json"),
+            ("/tmp/catalog.json", "/data/item.json", "/data/item.json"),
+            (
+                "https://example.com/stac/catalog.json",
+                "./a/collection.json",
+                "https://example.com/stac/a/collection.json",
+            ),
+            (
+                "https://example.com/stac/a/collection.json",
+                "../../item.json",
+                "https://example.com/item.json",
+            ),
+            (
+                "https://example.com/stac/catalog.json",
+                "/other/item.json",
+                "https://example.com/other/item.json",
+            ),
+            (
+                "data/catalog.json",
+                "https://example.com/item.json",
+                "https://example.com/item.json",
+            ),
+        ] {
+            assert_eq!(super::resolve(base, href), expected, "{base} + {href}");
+        }
+    }
+
+    #[tokio::test]
+    async fn crawl() {
+        let crawl = super::crawl("data/catalog/catalog.json").await.unwrap();
+        assert_eq!(crawl.collections.len(), 1);
+        assert_eq!(crawl.collections[0].id, "collection-id");
+        assert_eq!(
+            crawl.items,
+            vec![(
+                "data/catalog/collection-id/an-item/an-item.json".to_string(),
+                Some("collection-id".to_string())
+            )]
+        );
+        let item = super::read_item(&crawl.items[0].0, crawl.items[0].1.as_deref())
+            .await
+            .unwrap();
+        assert_eq!(item.collection.as_deref(), Some("collection-id"));
+        assert_eq!(
+            item.assets["data"].href,
+            "data/catalog/collection-id/an-item/data.tif"
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+/// Where a file load got to, persisted so an interrupted load can resume.
+///
+/// Written by a [Loader](super::Loader) with a
+/// [checkpoint](super::Loader::checkpoint) path after every write.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Files that have been completely loaded.
+    pub completed: Vec<PathBuf>,
+
+    /// The file that was being loaded, if any.
+    pub file: Option<PathBuf>,
+
+    /// The number of collections from `file` that have been written.
+    pub collections: u64,
+
+    /// The number of items from `file` that have been written.
+    pub items: u64,
+}
+
+impl Checkpoint {
+    /// Reads a checkpoint, or returns an empty one if the file doesn't exist.
+    pub(crate) async fn read(path: &Path) -> Result<Checkpoint> {
+        match tokio::fs::read(path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(Into::into),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Checkpoint::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Writes this checkpoint by way of a temporary file, so a crash never leaves half a checkpoint.
+    pub(crate) async fn write(&self, path: &Path) -> Result<()> {
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        tokio::fs::write(&tmp, serde_json::to_vec(self)?).await?;
+        tokio::fs::rename(&tmp, path).await.map_err(Into::into)
+    }
+
+    /// Moves on to `path`, unless it's the file that was already being loaded.
+    pub(crate) fn start(&mut self, path: &Path) {
+        if self.file.as_deref() != Some(path) {
+            self.file = Some(path.to_path_buf());
+            self.collections = 0;
+            self.items = 0;
+        }
+    }
+
+    /// Marks the current file as completely loaded.
+    pub(crate) fn complete(&mut self) {
+        if let Some(file) = self.file.take() {
+            self.completed.push(file);
+        }
+        self.collections = 0;
+        self.items = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Checkpoint;
+    use std::path::Path;
+
+    #[tokio::test]
+    async fn roundtrip() {
+        let path = std::env::temp_dir().join("pgstac-rs-checkpoint-roundtrip.json");
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(
+            Checkpoint::read(&path).await.unwrap(),
+            Checkpoint::default()
+        );
+        let mut checkpoint = Checkpoint::default();
+        checkpoint.start(Path::new("a.json"));
+        checkpoint.items = 42;
+        checkpoint.write(&path).await.unwrap();
+        assert_eq!(Checkpoint::read(&path).await.unwrap(), checkpoint);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn start_and_complete() {
+        let mut checkpoint = Checkpoint::default();
+        checkpoint.start(Path::new("a.json"));
+        checkpoint.items = 2;
+        checkpoint.start(Path::new("a.json"));
+        assert_eq!(checkpoint.items, 2);
+        checkpoint.complete();
+        checkpoint.start(Path::new("b.json"));
+        assert_eq!(checkpoint.items, 0);
+        assert_eq!(checkpoint.completed, vec![Path::new("a.json")]);
+    }
+}
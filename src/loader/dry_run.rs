@@ -0,0 +1,123 @@
+use chrono::{DateTime, FixedOffset, Utc};
+use stac::{Collection, Item};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+/// What a [dry run](super::Loader::dry_run) would have loaded.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DryRunReport {
+    /// The ids of the collections that would be loaded.
+    pub collections: BTreeSet<String>,
+
+    /// The number of items that would be loaded for each collection.
+    ///
+    /// Items without a collection are counted under `None`.
+    pub items: BTreeMap<Option<String>, u64>,
+
+    /// The earliest and latest datetimes of the items, considering
+    /// `datetime`, `start_datetime`, and `end_datetime`.
+    pub datetime: Option<(DateTime<Utc>, DateTime<Utc>)>,
+
+    /// Items that appeared more than once, as (collection, id) pairs.
+    pub duplicates: Vec<(Option<String>, String)>,
+
+    /// Items whose datetimes couldn't be parsed, as (collection, id) pairs.
+    pub invalid_datetimes: Vec<(Option<String>, String)>,
+
+    /// Collections that items refer to but that neither would be loaded nor
+    /// exist in the database.
+    ///
+    /// Filled in by [Loader::dry_run_report](super::Loader::dry_run_report).
+    pub missing_collections: BTreeSet<String>,
+}
+
+/// Collects a [DryRunReport] as items and collections go by.
+#[derive(Debug, Default)]
+pub(crate) struct DryRun {
+    report: DryRunReport,
+    seen: HashSet<(Option<String>, String)>,
+}
+
+impl DryRun {
+    pub(crate) fn add_collections(&mut self, collections: &[Collection]) {
+        self.report
+            .collections
+            .extend(collections.iter().map(|collection| collection.id.clone()));
+    }
+
+    pub(crate) fn add_items(&mut self, items: &[Item]) {
+        for item in items {
+            let key = (item.collection.clone(), item.id.clone());
+            *self.report.items.entry(key.0.clone()).or_default() += 1;
+            let properties = &item.properties;
+            for datetime in [
+                &properties.datetime,
+                &properties.start_datetime,
+                &properties.end_datetime,
+            ]
+            .into_iter()
+            .flatten()
+            {
+                match DateTime::<FixedOffset>::parse_from_rfc3339(datetime) {
+                    Ok(datetime) => self.add_datetime(datetime.with_timezone(&Utc)),
+                    Err(_) => {
+                        self.report.invalid_datetimes.push(key.clone());
+                        break;
+                    }
+                }
+            }
+            if !self.seen.insert(key.clone()) {
+                self.report.duplicates.push(key);
+            }
+        }
+    }
+
+    /// Returns the report and the collection ids that items refer to.
+    pub(crate) fn report(&self) -> (DryRunReport, BTreeSet<String>) {
+        let referenced = self.report.items.keys().flatten().cloned().collect();
+        (self.report.clone(), referenced)
+    }
+
+    fn add_datetime(&mut self, datetime: DateTime<Utc>) {
+        self.report.datetime = Some(match self.report.datetime {
+            Some((start, end)) => (start.min(datetime), end.max(datetime)),
+            None => (datetime, datetime),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DryRun;
+    use stac::{Collection, Item};
+
+    fn item(id: &str, collection: &str, datetime: &str) -> Item {
+        let mut item = Item::new(id).collection(collection);
+        item.properties.datetime = Some(datetime.to_string());
+        item
+    }
+
+    #[test]
+    fn add() {
+        let mut dry_run = DryRun::default();
+        dry_run.add_collections(&[Collection::new("one", "a description")]);
+        dry_run.add_items(&[
+            item("a", "one", "2023-01-02T00:00:00Z"),
+            item("b", "two", "2023-01-01T00:00:00+01:00"),
+            item("a", "one", "2023-03-01T00:00:00Z"),
+            item("c", "one", "not a datetime"),
+        ]);
+        let (report, referenced) = dry_run.report();
+        assert_eq!(report.collections.len(), 1);
+        assert_eq!(report.items[&Some("one".to_string())], 3);
+        assert_eq!(report.items[&Some("two".to_string())], 1);
+        let (start, end) = report.datetime.unwrap();
+        assert_eq!(start.to_rfc3339(), "2022-12-31T23:00:00+00:00");
+        assert_eq!(end.to_rfc3339(), "2023-03-01T00:00:00+00:00");
+        assert_eq!(
+            report.duplicates,
+            vec![(Some("one".to_string()), "a".to_string())]
+        );
+        assert_eq!(report.invalid_datetimes.len(), 1);
+        assert_eq!(referenced.len(), 2);
+    }
+}
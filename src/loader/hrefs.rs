@@ -0,0 +1,88 @@
+use stac::{Asset, Collection, Item, Link};
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Formatter},
+    sync::Arc,
+};
+
+type Rewrite = Arc<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+/// Rewrites asset and link hrefs before anything is written.
+#[derive(Clone, Default)]
+pub(crate) struct HrefRewriter(Option<Rewrite>);
+
+impl HrefRewriter {
+    pub(crate) fn new(
+        rewrite: impl Fn(&str) -> Option<String> + Send + Sync + 'static,
+    ) -> HrefRewriter {
+        HrefRewriter(Some(Arc::new(rewrite)))
+    }
+
+    pub(crate) fn item(&self, mut item: Item) -> Item {
+        self.rewrite(&mut item.assets, &mut item.links);
+        item
+    }
+
+    pub(crate) fn collection(&self, mut collection: Collection) -> Collection {
+        self.rewrite(&mut collection.assets, &mut collection.links);
+        collection
+    }
+
+    fn rewrite(&self, assets: &mut HashMap<String, Asset>, links: &mut [Link]) {
+        let Some(rewrite) = &self.0 else {
+            return;
+        };
+        let hrefs = assets
+            .values_mut()
+            .map(|asset| &mut asset.href)
+            .chain(links.iter_mut().map(|link| &mut link.href));
+        for href in hrefs {
+            if let Some(rewritten) = rewrite(href) {
+                *href = rewritten;
+            }
+        }
+    }
+}
+
+impl Debug for HrefRewriter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("HrefRewriter")
+            .field(&self.0.as_ref().map(|_| "Fn(&str) -> Option<String>"))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HrefRewriter;
+    use stac::{Asset, Item, Link};
+
+    #[test]
+    fn item() {
+        let mut item = Item::new("an-id");
+        let _ = item
+            .assets
+            .insert("data".to_string(), Asset::new("s3://bucket/data.tif"));
+        let _ = item.assets.insert(
+            "other".to_string(),
+            Asset::new("https://example.com/other.tif"),
+        );
+        item.links
+            .push(Link::new("s3://bucket/parent.json", "parent"));
+        let rewriter = HrefRewriter::new(|href| {
+            href.strip_prefix("s3://bucket/")
+                .map(|key| format!("https://bucket.s3.amazonaws.com/{key}"))
+        });
+        let item = rewriter.item(item);
+        assert_eq!(
+            item.assets["data"].href,
+            "https://bucket.s3.amazonaws.com/data.tif"
+        );
+        assert_eq!(item.assets["other"].href, "https://example.com/other.tif");
+        assert_eq!(
+            item.links[0].href,
+            "https://bucket.s3.amazonaws.com/parent.json"
+        );
+        assert_eq!(HrefRewriter::default().item(item.clone()), item);
+    }
+}
@@ -0,0 +1,152 @@
+use std::{
+    fmt::{Debug, Formatter},
+    io::Result,
+    path::PathBuf,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+use tokio::io::{AsyncBufRead, AsyncRead, ReadBuf};
+
+/// How far a [Loader](super::Loader) has gotten.
+///
+/// Counts are totals over the loader's lifetime, not just the current call.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Progress {
+    /// The number of collections loaded.
+    pub collections: u64,
+
+    /// The number of items loaded.
+    pub items: u64,
+
+    /// The number of bytes read from files and readers.
+    ///
+    /// For compressed files, this counts compressed bytes, so it can be
+    /// compared against the file's size.
+    pub bytes: u64,
+
+    /// The file being loaded, if any.
+    pub file: Option<PathBuf>,
+
+    /// The number of writes that failed.
+    pub errors: u64,
+}
+
+type Callback = Arc<dyn Fn(&Progress) + Send + Sync>;
+
+/// Shared progress state plus the caller's callback, if any.
+#[derive(Clone, Default)]
+pub(crate) struct Tracker {
+    progress: Arc<Mutex<Progress>>,
+    callback: Option<Callback>,
+}
+
+/// Counts the bytes read through an inner reader.
+pub(crate) struct Counted<R> {
+    inner: R,
+    tracker: Tracker,
+}
+
+impl Tracker {
+    pub(crate) fn set_callback(&mut self, callback: impl Fn(&Progress) + Send + Sync + 'static) {
+        self.callback = Some(Arc::new(callback));
+    }
+
+    pub(crate) fn progress(&self) -> Progress {
+        self.progress.lock().unwrap().clone()
+    }
+
+    /// Updates the progress and then calls the callback.
+    pub(crate) fn update(&self, f: impl FnOnce(&mut Progress)) {
+        let progress = {
+            let mut progress = self.progress.lock().unwrap();
+            f(&mut progress);
+            progress.clone()
+        };
+        if let Some(callback) = &self.callback {
+            callback(&progress);
+        }
+    }
+
+    /// Counts bytes without calling the callback, since reads are frequent.
+    fn add_bytes(&self, bytes: usize) {
+        self.progress.lock().unwrap().bytes += bytes as u64;
+    }
+}
+
+impl Debug for Tracker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tracker")
+            .field("progress", &self.progress)
+            .field("callback", &self.callback.as_ref().map(|_| "Fn(&Progress)"))
+            .finish()
+    }
+}
+
+impl<R> Counted<R> {
+    pub(crate) fn new(inner: R, tracker: &Tracker) -> Counted<R> {
+        Counted {
+            inner,
+            tracker: tracker.clone(),
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for Counted<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<()>> {
+        let before = buf.filled().len();
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+        self.tracker.add_bytes(buf.filled().len() - before);
+        poll
+    }
+}
+
+impl<R: AsyncBufRead + Unpin> AsyncBufRead for Counted<R> {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<&[u8]>> {
+        Pin::new(&mut self.get_mut().inner).poll_fill_buf(cx)
+    }
+
+    fn consume(mut self: Pin<&mut Self>, amt: usize) {
+        self.tracker.add_bytes(amt);
+        Pin::new(&mut self.inner).consume(amt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Counted, Tracker};
+    use std::sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    };
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt};
+
+    #[tokio::test]
+    async fn counted() {
+        let tracker = Tracker::default();
+        let mut lines = Counted::new("a\nbc\n".as_bytes(), &tracker).lines();
+        while lines.next_line().await.unwrap().is_some() {}
+        assert_eq!(tracker.progress().bytes, 5);
+        let mut buf = Vec::new();
+        let _ = Counted::new("def".as_bytes(), &tracker)
+            .read_to_end(&mut buf)
+            .await
+            .unwrap();
+        assert_eq!(tracker.progress().bytes, 8);
+    }
+
+    #[test]
+    fn update_calls_callback() {
+        let items = Arc::new(AtomicU64::new(0));
+        let mut tracker = Tracker::default();
+        let seen = items.clone();
+        tracker.set_callback(move |progress| seen.store(progress.items, Ordering::SeqCst));
+        tracker.update(|progress| progress.items += 2);
+        tracker.update(|progress| progress.items += 3);
+        assert_eq!(items.load(Ordering::SeqCst), 5);
+    }
+}
@@ -0,0 +1,84 @@
+use crate::{Error, Result};
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Limits how many items per second are written, across every loader that
+/// shares it.
+///
+/// Each write is scheduled to start once the writes before it have had
+/// enough time for their items at the limit, so a load never bursts above
+/// it.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Throttle(Option<Arc<Inner>>);
+
+#[derive(Debug)]
+struct Inner {
+    items_per_second: f64,
+    next: Mutex<Option<Instant>>,
+}
+
+impl Throttle {
+    /// Returns an error unless `items_per_second` is finite and greater than zero.
+    pub(crate) fn new(items_per_second: f64) -> Result<Throttle> {
+        if !(items_per_second.is_finite() && items_per_second > 0.0) {
+            return Err(Error::InvalidParameter {
+                name: "max_items_per_second".to_string(),
+                reason: format!(
+                    "{} isn't a finite number greater than zero",
+                    items_per_second
+                ),
+            });
+        }
+        Ok(Throttle(Some(Arc::new(Inner {
+            items_per_second,
+            next: Mutex::new(None),
+        }))))
+    }
+
+    /// Waits until `items` more items can be written.
+    pub(crate) async fn acquire(&self, items: u64) {
+        let Some(inner) = &self.0 else {
+            return;
+        };
+        let delay = {
+            let mut next = inner.next.lock().unwrap();
+            let now = Instant::now();
+            let start = next.map_or(now, |next| next.max(now));
+            *next = Some(start + Duration::from_secs_f64(items as f64 / inner.items_per_second));
+            start - now
+        };
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Throttle;
+    use std::time::{Duration, Instant};
+
+    #[tokio::test]
+    async fn acquire() {
+        let start = Instant::now();
+        Throttle::default().acquire(1_000_000).await;
+        assert!(start.elapsed() < Duration::from_millis(100));
+
+        let throttle = Throttle::new(1000.0).unwrap();
+        let shared = throttle.clone();
+        throttle.acquire(100).await;
+        assert!(start.elapsed() < Duration::from_millis(100));
+        shared.acquire(100).await;
+        throttle.acquire(100).await;
+        assert!(start.elapsed() >= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn invalid() {
+        for items_per_second in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(Throttle::new(items_per_second).is_err());
+        }
+    }
+}
@@ -0,0 +1,14 @@
+use clap::Parser;
+use pgstac::cli::Args;
+use std::process::ExitCode;
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    match Args::parse().run().await {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,454 @@
+//! An in-memory [Pgstac] implementation for tests.
+//!
+//! [MockClient] keeps collections and items in hash maps, so code that
+//! depends on the [Pgstac] trait can be unit tested without a database.
+//! Searches support `ids`, `collections`, `bbox`, `datetime`, `limit`, and
+//! paging tokens; anything else returns [Error::InvalidParameter] rather than
+//! being silently ignored.
+//!
+//! # Examples
+//!
+//! ```
+//! use pgstac::{mock::MockClient, Pgstac};
+//! use stac::{Collection, Item};
+//! use stac_api::Search;
+//!
+//! # tokio_test::block_on(async {
+//! let client = MockClient::new();
+//! client
+//!     .add_collection(Collection::new("collection-id", "a description"))
+//!     .await
+//!     .unwrap();
+//! client
+//!     .add_item(Item::new("an-id").collection("collection-id"))
+//!     .await
+//!     .unwrap();
+//! let search = Search {
+//!     ids: Some(vec!["an-id".to_string()]),
+//!     ..Default::default()
+//! };
+//! let page = client.search(search).await.unwrap();
+//! assert_eq!(page.features.len(), 1);
+//! # })
+//! ```
+
+use crate::{client::is_newer, Error, OnConflict, Page, Pgstac, Result};
+use futures::future::BoxFuture;
+use serde_json::{Map, Value};
+use stac::{Collection, Item};
+use stac_api::{Context, Search};
+use std::{collections::HashMap, sync::Mutex};
+
+/// The page size used when a search doesn't have a limit, like **pgstac**'s.
+const DEFAULT_LIMIT: u64 = 10;
+
+/// An in-memory stand-in for a [Client](crate::Client).
+#[derive(Debug, Default)]
+pub struct MockClient {
+    collections: Mutex<HashMap<String, Collection>>,
+    items: Mutex<HashMap<(String, String), Item>>,
+}
+
+impl MockClient {
+    /// Creates an empty mock client.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pgstac::mock::MockClient;
+    /// let client = MockClient::new();
+    /// ```
+    pub fn new() -> MockClient {
+        MockClient::default()
+    }
+
+    fn insert_collection(&self, collection: Collection, upsert: bool) -> Result<()> {
+        let mut collections = self.collections.lock().unwrap();
+        if !upsert && collections.contains_key(&collection.id) {
+            return Err(Error::DuplicateCollection(collection.id));
+        }
+        let _ = collections.insert(collection.id.clone(), collection);
+        Ok(())
+    }
+
+    fn write(&self, items: &[Item], on_conflict: OnConflict) -> Result<()> {
+        let collections = self.collections.lock().unwrap();
+        let mut stored = self.items.lock().unwrap();
+        for item in items {
+            let collection = item.collection.clone().unwrap_or_default();
+            if !collections.contains_key(&collection) {
+                return Err(Error::CollectionNotFound(collection));
+            }
+            if on_conflict == OnConflict::Error && stored.contains_key(&key(item)) {
+                return Err(Error::DuplicateItem {
+                    collection: item.collection.clone(),
+                    id: item.id.clone(),
+                });
+            }
+        }
+        for item in items {
+            let key = key(item);
+            let write = match (on_conflict, stored.get(&key)) {
+                (_, None) | (OnConflict::Error | OnConflict::Upsert, Some(_)) => true,
+                (OnConflict::Ignore, Some(_)) => false,
+                (OnConflict::UpdateIfNewer, Some(existing)) => is_newer(
+                    item.properties.updated.as_deref(),
+                    existing.properties.updated.as_deref(),
+                ),
+            };
+            if write {
+                let _ = stored.insert(key, item.clone());
+            }
+        }
+        Ok(())
+    }
+
+    fn matches(&self, search: &Search) -> Result<Vec<Item>> {
+        unsupported(search)?;
+        let bbox = search.bbox.as_deref().map(rect).transpose()?;
+        let datetime = search
+            .datetime
+            .as_deref()
+            .map(|datetime| {
+                stac::datetime::parse(datetime).map_err(|err| Error::InvalidParameter {
+                    name: "datetime".to_string(),
+                    reason: err.to_string(),
+                })
+            })
+            .transpose()?;
+        let mut items = Vec::new();
+        for item in self.items.lock().unwrap().values() {
+            if let Some(ids) = &search.ids {
+                if !ids.contains(&item.id) {
+                    continue;
+                }
+            }
+            if let Some(collections) = &search.collections {
+                if !item
+                    .collection
+                    .as_ref()
+                    .is_some_and(|collection| collections.contains(collection))
+                {
+                    continue;
+                }
+            }
+            if let Some(bbox) = bbox {
+                let item_bbox = item.bbox.as_deref().map(rect).transpose()?;
+                if !item_bbox.is_some_and(|item_bbox| overlaps(bbox, item_bbox)) {
+                    continue;
+                }
+            }
+            if let Some((start, end)) = datetime {
+                // Items with unparseable datetimes can't match, as in the database.
+                if !item.intersects_datetimes(start, end).unwrap_or(false) {
+                    continue;
+                }
+            }
+            items.push(item.clone());
+        }
+        // pgstac's default order.
+        items.sort_by(|a, b| {
+            b.properties
+                .datetime
+                .cmp(&a.properties.datetime)
+                .then_with(|| b.id.cmp(&a.id))
+        });
+        Ok(items)
+    }
+}
+
+impl Pgstac for MockClient {
+    fn version(&self) -> BoxFuture<'_, Result<String>> {
+        Box::pin(async { Ok(format!("mock-{}", env!("CARGO_PKG_VERSION"))) })
+    }
+
+    fn collections(&self) -> BoxFuture<'_, Result<Vec<Collection>>> {
+        Box::pin(async {
+            let mut collections = self
+                .collections
+                .lock()
+                .unwrap()
+                .values()
+                .cloned()
+                .collect::<Vec<_>>();
+            collections.sort_by(|a, b| a.id.cmp(&b.id));
+            Ok(collections)
+        })
+    }
+
+    fn collection<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<Option<Collection>>> {
+        Box::pin(async move { Ok(self.collections.lock().unwrap().get(id).cloned()) })
+    }
+
+    fn add_collection(&self, collection: Collection) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move { self.insert_collection(collection, false) })
+    }
+
+    fn upsert_collection(&self, collection: Collection) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move { self.insert_collection(collection, true) })
+    }
+
+    fn update_collection(&self, collection: Collection) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            let mut collections = self.collections.lock().unwrap();
+            match collections.get_mut(&collection.id) {
+                Some(stored) => {
+                    *stored = collection;
+                    Ok(())
+                }
+                None => Err(Error::CollectionNotFound(collection.id)),
+            }
+        })
+    }
+
+    fn delete_collection<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            if self.collections.lock().unwrap().remove(id).is_none() {
+                return Err(Error::CollectionNotFound(id.to_string()));
+            }
+            self.items
+                .lock()
+                .unwrap()
+                .retain(|(collection, _), _| collection != id);
+            Ok(())
+        })
+    }
+
+    fn item<'a>(&'a self, id: &'a str, collection: &'a str) -> BoxFuture<'a, Result<Option<Item>>> {
+        Box::pin(async move {
+            let key = (collection.to_string(), id.to_string());
+            Ok(self.items.lock().unwrap().get(&key).cloned())
+        })
+    }
+
+    fn add_item(&self, item: Item) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move { self.write(&[item], OnConflict::Error) })
+    }
+
+    fn add_items<'a>(&'a self, items: &'a [Item]) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move { self.write(items, OnConflict::Error) })
+    }
+
+    fn write_items<'a>(
+        &'a self,
+        items: &'a [Item],
+        on_conflict: OnConflict,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move { self.write(items, on_conflict) })
+    }
+
+    fn update_item(&self, item: Item) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            let mut items = self.items.lock().unwrap();
+            match items.get_mut(&key(&item)) {
+                Some(stored) => {
+                    *stored = item;
+                    Ok(())
+                }
+                None => Err(Error::ItemNotFound {
+                    collection: item.collection.unwrap_or_default(),
+                    id: item.id,
+                }),
+            }
+        })
+    }
+
+    fn upsert_item(&self, item: Item) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move { self.write(&[item], OnConflict::Upsert) })
+    }
+
+    fn upsert_items<'a>(&'a self, items: &'a [Item]) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move { self.write(items, OnConflict::Upsert) })
+    }
+
+    fn search(&self, search: Search) -> BoxFuture<'_, Result<Page>> {
+        Box::pin(async move {
+            let items = self.matches(&search)?;
+            let limit = search.limit.unwrap_or(DEFAULT_LIMIT).max(1) as usize;
+            let offset = match search.additional_fields.get("token") {
+                Some(token) => offset(token, limit)?,
+                None => 0,
+            };
+            let end = (offset + limit).min(items.len());
+            let start = offset.min(end);
+            let features = items[start..end]
+                .iter()
+                .map(|item| match serde_json::to_value(item)? {
+                    Value::Object(object) => Ok(object),
+                    _ => unreachable!("items serialize to objects"),
+                })
+                .collect::<Result<Vec<Map<String, Value>>>>()?;
+            Ok(Page {
+                context: Context {
+                    returned: features.len() as u64,
+                    limit: Some(limit as u64),
+                    matched: Some(items.len() as u64),
+                    additional_fields: Map::new(),
+                },
+                features,
+                next: (end < items.len()).then(|| end.to_string()),
+                prev: (start > 0).then(|| start.to_string()),
+            })
+        })
+    }
+}
+
+fn key(item: &Item) -> (String, String) {
+    (item.collection.clone().unwrap_or_default(), item.id.clone())
+}
+
+/// Errors on the search parameters that the mock doesn't implement.
+fn unsupported(search: &Search) -> Result<()> {
+    let unsupported = [
+        ("intersects", search.intersects.is_some()),
+        ("fields", search.fields.is_some()),
+        ("sortby", search.sortby.is_some()),
+        ("filter", search.filter.is_some()),
+        ("query", search.query.is_some()),
+    ];
+    match unsupported.into_iter().find(|(_, set)| *set) {
+        Some((name, _)) => Err(Error::InvalidParameter {
+            name: name.to_string(),
+            reason: "not supported by MockClient".to_string(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Returns a bbox's `[xmin, ymin, xmax, ymax]`, dropping any z.
+fn rect(bbox: &[f64]) -> Result<[f64; 4]> {
+    match *bbox {
+        [xmin, ymin, xmax, ymax] | [xmin, ymin, _, xmax, ymax, _] => Ok([xmin, ymin, xmax, ymax]),
+        _ => Err(Error::InvalidParameter {
+            name: "bbox".to_string(),
+            reason: format!("expected 4 or 6 values, got {}", bbox.len()),
+        }),
+    }
+}
+
+fn overlaps(a: [f64; 4], b: [f64; 4]) -> bool {
+    a[0] <= b[2] && b[0] <= a[2] && a[1] <= b[3] && b[1] <= a[3]
+}
+
+/// Turns a `next:` or `prev:` token into the offset of the page it points to.
+fn offset(token: &Value, limit: usize) -> Result<usize> {
+    let invalid = || Error::InvalidParameter {
+        name: "token".to_string(),
+        reason: format!("not a token from this client: {}", token),
+    };
+    let token = token.as_str().ok_or_else(invalid)?;
+    if let Some(next) = token.strip_prefix("next:") {
+        next.parse().map_err(|_| invalid())
+    } else if let Some(prev) = token.strip_prefix("prev:") {
+        let prev: usize = prev.parse().map_err(|_| invalid())?;
+        Ok(prev.saturating_sub(limit))
+    } else {
+        Err(invalid())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MockClient;
+    use crate::{Error, Pgstac};
+    use futures::TryStreamExt;
+    use stac::{Collection, Item};
+    use stac_api::Search;
+
+    async fn client() -> MockClient {
+        let client = MockClient::new();
+        client
+            .add_collection(Collection::new("collection-id", "a description"))
+            .await
+            .unwrap();
+        for (id, datetime, bbox) in [
+            ("a", "2023-01-01T00:00:00Z", vec![0., 0., 1., 1.]),
+            ("b", "2023-02-01T00:00:00Z", vec![10., 10., 11., 11.]),
+            ("c", "2023-03-01T00:00:00Z", vec![0.5, 0.5, 2., 2.]),
+        ] {
+            let mut item = Item::new(id).collection("collection-id");
+            item.properties.datetime = Some(datetime.to_string());
+            item.bbox = Some(bbox);
+            client.add_item(item).await.unwrap();
+        }
+        client
+    }
+
+    fn ids(page: &crate::Page) -> Vec<&str> {
+        page.features
+            .iter()
+            .map(|item| item["id"].as_str().unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn writes() {
+        let client = client().await;
+        assert!(matches!(
+            client
+                .add_item(Item::new("a").collection("collection-id"))
+                .await
+                .unwrap_err(),
+            Error::DuplicateItem { .. }
+        ));
+        assert!(matches!(
+            client
+                .add_item(Item::new("a").collection("not-a-collection"))
+                .await
+                .unwrap_err(),
+            Error::CollectionNotFound(_)
+        ));
+        assert!(matches!(
+            client
+                .update_item(Item::new("d").collection("collection-id"))
+                .await
+                .unwrap_err(),
+            Error::ItemNotFound { .. }
+        ));
+        client.delete_collection("collection-id").await.unwrap();
+        assert!(client.item("a", "collection-id").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn search() {
+        let client = client().await;
+        let page = client.search(Search::default()).await.unwrap();
+        assert_eq!(ids(&page), ["c", "b", "a"]);
+
+        let search = Search {
+            bbox: Some(vec![0.9, 0.9, 3., 3.]),
+            datetime: Some("2023-02-15T00:00:00Z/..".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(ids(&client.search(search).await.unwrap()), ["c"]);
+
+        let search = Search {
+            ids: Some(vec!["a".to_string(), "b".to_string()]),
+            collections: Some(vec!["collection-id".to_string()]),
+            ..Default::default()
+        };
+        assert_eq!(ids(&client.search(search).await.unwrap()), ["b", "a"]);
+
+        let search = Search {
+            sortby: Some(Vec::new()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            client.search(search).await.unwrap_err(),
+            Error::InvalidParameter { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn pages() {
+        let client = client().await;
+        let search = Search {
+            limit: Some(2),
+            ..Default::default()
+        };
+        let pages = client.pages(search).try_collect::<Vec<_>>().await.unwrap();
+        assert_eq!(pages.len(), 2);
+        assert_eq!(ids(&pages[1]), ["a"]);
+        assert_eq!(pages[1].prev_token().unwrap(), "prev:2");
+    }
+}
@@ -1,5 +1,6 @@
 use serde::Deserialize;
-use stac_api::{Context, Item};
+use serde_json::{Map, Value};
+use stac_api::{Item, Search};
 
 /// A page of search results.
 #[derive(Debug, Deserialize)]
@@ -15,7 +16,69 @@ pub struct Page {
     pub prev: Option<String>,
 
     /// The search context.
-    pub context: Context,
+    pub context: stac_api::Context,
+}
+
+/// How a search counts the items that match it.
+///
+/// Counting every match can take longer than the search itself. By default,
+/// **pgstac**'s `context` setting decides for every search; applying a
+/// context to a search overrides it for just that search, through the
+/// search's `conf`.
+///
+/// # Examples
+///
+/// ```
+/// use pgstac::Context;
+/// use stac_api::Search;
+///
+/// let mut search = Search::default();
+/// Context::Estimate { threshold: 10000 }.apply(&mut search);
+/// assert_eq!(search.additional_fields["conf"]["context"], "auto");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Context {
+    /// Count every match, so [Page::matched] is exact.
+    On,
+
+    /// Don't count matches, so [Page::matched] is `None`.
+    Off,
+
+    /// Count every match unless the query planner estimates there are more
+    /// than `threshold`, in which case [Page::matched] is that estimate.
+    Estimate {
+        /// The estimated number of matches above which counting is skipped.
+        threshold: u64,
+    },
+}
+
+impl Context {
+    /// Sets this context in a search's `conf`, keeping any other settings
+    /// already there.
+    pub fn apply(&self, search: &mut Search) {
+        let conf = search
+            .additional_fields
+            .entry("conf")
+            .or_insert_with(|| Value::Object(Map::new()));
+        if !conf.is_object() {
+            *conf = Value::Object(Map::new());
+        }
+        let conf = conf.as_object_mut().expect("conf was just made an object");
+        let (context, threshold) = match self {
+            Context::On => ("on", None),
+            Context::Off => ("off", None),
+            Context::Estimate { threshold } => ("auto", Some(*threshold)),
+        };
+        let _ = conf.insert("context".to_string(), context.into());
+        match threshold {
+            Some(threshold) => {
+                let _ = conf.insert("context_estimated_count".to_string(), threshold.into());
+            }
+            None => {
+                let _ = conf.remove("context_estimated_count");
+            }
+        }
+    }
 }
 
 impl Page {
@@ -56,4 +119,37 @@ impl Page {
     pub fn prev_token(&self) -> Option<String> {
         self.prev.as_ref().map(|prev| format!("prev:{}", prev))
     }
+
+    /// Returns the number of items that match the search, if it was counted.
+    ///
+    /// Depending on the search's [Context], this is an exact count, an
+    /// estimate, or `None`.
+    pub fn matched(&self) -> Option<u64> {
+        self.context.matched
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Context;
+    use serde_json::json;
+    use stac_api::Search;
+
+    #[test]
+    fn apply_context() {
+        let mut search = Search::default();
+        let _ = search
+            .additional_fields
+            .insert("conf".to_string(), json!({"nohydrate": true}));
+        Context::Estimate { threshold: 100 }.apply(&mut search);
+        assert_eq!(
+            search.additional_fields["conf"],
+            json!({"nohydrate": true, "context": "auto", "context_estimated_count": 100})
+        );
+        Context::Off.apply(&mut search);
+        assert_eq!(
+            search.additional_fields["conf"],
+            json!({"nohydrate": true, "context": "off"})
+        );
+    }
 }
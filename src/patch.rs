@@ -0,0 +1,434 @@
+//! Patching items and collections.
+//!
+//! The STAC [Transaction
+//! extension](https://github.com/stac-api-extensions/transaction) lets
+//! clients `PATCH` items and collections with either an [RFC
+//! 7386](https://datatracker.ietf.org/doc/html/rfc7386) merge patch or an
+//! [RFC 6902](https://datatracker.ietf.org/doc/html/rfc6902) JSON patch. A
+//! [Patch] is either one, and applies to any JSON value.
+
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A patch to an item or collection.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Patch {
+    /// An RFC 7386 JSON merge patch.
+    Merge(Value),
+
+    /// An RFC 6902 JSON patch.
+    Json(Vec<Operation>),
+}
+
+/// An RFC 6902 JSON patch operation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum Operation {
+    /// Adds a value to an object or inserts it into an array.
+    Add {
+        /// Where to add the value.
+        path: String,
+
+        /// The value to add.
+        value: Value,
+    },
+
+    /// Removes a value.
+    Remove {
+        /// The value to remove.
+        path: String,
+    },
+
+    /// Replaces a value.
+    Replace {
+        /// The value to replace.
+        path: String,
+
+        /// The new value.
+        value: Value,
+    },
+
+    /// Moves a value.
+    Move {
+        /// The value to move.
+        from: String,
+
+        /// Where to move it.
+        path: String,
+    },
+
+    /// Copies a value.
+    Copy {
+        /// The value to copy.
+        from: String,
+
+        /// Where to copy it.
+        path: String,
+    },
+
+    /// Checks that a value is what's expected, failing the patch if not.
+    Test {
+        /// The value to check.
+        path: String,
+
+        /// The expected value.
+        value: Value,
+    },
+}
+
+/// Why a JSON patch operation failed.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum OperationError {
+    /// A path isn't a valid JSON pointer, or names an invalid array index.
+    #[error("invalid path: {0}")]
+    InvalidPath(String),
+
+    /// Nothing exists at a path that must exist.
+    #[error("nothing at {0}")]
+    NotFound(String),
+
+    /// A value can't be moved into one of its own children.
+    #[error("can't move {from} into {path}")]
+    MoveIntoChild {
+        /// The value being moved.
+        from: String,
+
+        /// Where it was being moved.
+        path: String,
+    },
+
+    /// A `test` operation didn't match, i.e. the value changed.
+    #[error("test failed at {0}")]
+    TestFailed(String),
+}
+
+impl Patch {
+    /// Applies this patch.
+    ///
+    /// JSON patches are atomic: if any operation fails, the target is left
+    /// unchanged and [Error::Patch] says which operation failed and why.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pgstac::patch::{Operation, Patch};
+    /// use serde_json::json;
+    ///
+    /// let mut item = json!({"id": "an-id", "properties": {"title": "a title"}});
+    /// let patch = Patch::Json(vec![Operation::Replace {
+    ///     path: "/properties/title".to_string(),
+    ///     value: "a new title".into(),
+    /// }]);
+    /// patch.apply(&mut item).unwrap();
+    /// assert_eq!(item["properties"]["title"], "a new title");
+    /// ```
+    pub fn apply(&self, target: &mut Value) -> Result<()> {
+        match self {
+            Patch::Merge(patch) => {
+                merge(target, patch);
+                Ok(())
+            }
+            Patch::Json(operations) => {
+                let mut patched = target.clone();
+                for (index, operation) in operations.iter().enumerate() {
+                    operation
+                        .apply(&mut patched)
+                        .map_err(|source| Error::Patch { index, source })?;
+                }
+                *target = patched;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl From<Value> for Patch {
+    fn from(value: Value) -> Patch {
+        Patch::Merge(value)
+    }
+}
+
+impl From<Vec<Operation>> for Patch {
+    fn from(operations: Vec<Operation>) -> Patch {
+        Patch::Json(operations)
+    }
+}
+
+impl Operation {
+    /// Applies this operation.
+    pub fn apply(&self, target: &mut Value) -> std::result::Result<(), OperationError> {
+        match self {
+            Operation::Add { path, value } => add(target, path, value.clone()),
+            Operation::Remove { path } => remove(target, path).map(|_| ()),
+            Operation::Replace { path, value } => {
+                *target
+                    .pointer_mut(path)
+                    .ok_or_else(|| OperationError::NotFound(path.clone()))? = value.clone();
+                Ok(())
+            }
+            Operation::Move { from, path } => {
+                if path.starts_with(&format!("{}/", from)) {
+                    return Err(OperationError::MoveIntoChild {
+                        from: from.clone(),
+                        path: path.clone(),
+                    });
+                }
+                if from != path {
+                    let value = remove(target, from)?;
+                    add(target, path, value)?;
+                }
+                Ok(())
+            }
+            Operation::Copy { from, path } => {
+                let value = target
+                    .pointer(from)
+                    .cloned()
+                    .ok_or_else(|| OperationError::NotFound(from.clone()))?;
+                add(target, path, value)
+            }
+            Operation::Test { path, value } => {
+                if target.pointer(path) == Some(value) {
+                    Ok(())
+                } else {
+                    Err(OperationError::TestFailed(path.clone()))
+                }
+            }
+        }
+    }
+}
+
+/// Applies an [RFC 7386](https://datatracker.ietf.org/doc/html/rfc7386) JSON merge patch.
+///
+/// Objects in the patch are merged into the target recursively, `null`s
+/// remove members, and anything else replaces the target's value.
+///
+/// # Examples
+///
+/// ```
+/// use serde_json::json;
+///
+/// let mut item = json!({"id": "an-id", "properties": {"title": "a title", "foo": "bar"}});
+/// pgstac::patch::merge(&mut item, &json!({"properties": {"title": null, "foo": "baz"}}));
+/// assert_eq!(item, json!({"id": "an-id", "properties": {"foo": "baz"}}));
+/// ```
+pub fn merge(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Default::default());
+    }
+    let Value::Object(target) = target else {
+        unreachable!()
+    };
+    for (key, value) in patch {
+        if value.is_null() {
+            let _ = target.remove(key);
+        } else {
+            merge(target.entry(key).or_insert(Value::Null), value);
+        }
+    }
+}
+
+fn add(target: &mut Value, path: &str, value: Value) -> std::result::Result<(), OperationError> {
+    if path.is_empty() {
+        *target = value;
+        return Ok(());
+    }
+    let (parent, token) = split(path)?;
+    match target.pointer_mut(parent) {
+        Some(Value::Object(object)) => {
+            let _ = object.insert(token, value);
+            Ok(())
+        }
+        Some(Value::Array(array)) => {
+            let index = if token == "-" {
+                array.len()
+            } else {
+                index(&token)
+                    .filter(|&index| index <= array.len())
+                    .ok_or_else(|| OperationError::InvalidPath(path.to_string()))?
+            };
+            array.insert(index, value);
+            Ok(())
+        }
+        _ => Err(OperationError::NotFound(path.to_string())),
+    }
+}
+
+fn remove(target: &mut Value, path: &str) -> std::result::Result<Value, OperationError> {
+    let (parent, token) = split(path)?;
+    match target.pointer_mut(parent) {
+        Some(Value::Object(object)) => object.remove(&token),
+        Some(Value::Array(array)) => index(&token)
+            .filter(|&index| index < array.len())
+            .map(|index| array.remove(index)),
+        _ => None,
+    }
+    .ok_or_else(|| OperationError::NotFound(path.to_string()))
+}
+
+/// Splits a JSON pointer into its parent and its unescaped last token.
+fn split(path: &str) -> std::result::Result<(&str, String), OperationError> {
+    match path.rfind('/') {
+        Some(i) if path.starts_with('/') => Ok((
+            &path[..i],
+            path[i + 1..].replace("~1", "/").replace("~0", "~"),
+        )),
+        _ => Err(OperationError::InvalidPath(path.to_string())),
+    }
+}
+
+fn index(token: &str) -> Option<usize> {
+    if token.is_empty()
+        || !token.bytes().all(|b| b.is_ascii_digit())
+        || (token.len() > 1 && token.starts_with('0'))
+    {
+        None
+    } else {
+        token.parse().ok()
+    }
+}
+
+/// Checks that a patch left a field the way it was.
+pub(crate) fn check_unchanged(value: &Value, field: &'static str, expected: &str) -> Result<()> {
+    if value.get(field).and_then(Value::as_str) == Some(expected) {
+        Ok(())
+    } else {
+        Err(Error::ImmutableField(field))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Operation, OperationError, Patch};
+    use crate::Error;
+    use serde_json::json;
+
+    // The examples from RFC 7386, appendix A.
+    #[test]
+    fn merge() {
+        let cases = [
+            (json!({"a": "b"}), json!({"a": "c"}), json!({"a": "c"})),
+            (
+                json!({"a": "b"}),
+                json!({"b": "c"}),
+                json!({"a": "b", "b": "c"}),
+            ),
+            (json!({"a": "b"}), json!({"a": null}), json!({})),
+            (
+                json!({"a": "b", "b": "c"}),
+                json!({"a": null}),
+                json!({"b": "c"}),
+            ),
+            (json!({"a": ["b"]}), json!({"a": "c"}), json!({"a": "c"})),
+            (json!({"a": "c"}), json!({"a": ["b"]}), json!({"a": ["b"]})),
+            (
+                json!({"a": {"b": "c"}}),
+                json!({"a": {"b": "d", "c": null}}),
+                json!({"a": {"b": "d"}}),
+            ),
+            (
+                json!({"a": [{"b": "c"}]}),
+                json!({"a": [1]}),
+                json!({"a": [1]}),
+            ),
+            (json!(["a", "b"]), json!(["c", "d"]), json!(["c", "d"])),
+            (json!({"a": "b"}), json!(["c"]), json!(["c"])),
+            (json!({"a": "foo"}), json!(null), json!(null)),
+            (json!({"a": "foo"}), json!("bar"), json!("bar")),
+            (
+                json!({"e": null}),
+                json!({"a": 1}),
+                json!({"e": null, "a": 1}),
+            ),
+            (
+                json!([1, 2]),
+                json!({"a": "b", "c": null}),
+                json!({"a": "b"}),
+            ),
+            (
+                json!({}),
+                json!({"a": {"bb": {"ccc": null}}}),
+                json!({"a": {"bb": {}}}),
+            ),
+        ];
+        for (mut target, patch, expected) in cases {
+            super::merge(&mut target, &patch);
+            assert_eq!(target, expected);
+        }
+    }
+
+    #[test]
+    fn json() {
+        let mut target = json!({"foo": ["bar", "baz"], "a/b": 1, "qux": {"m~n": 2}});
+        let operations: Vec<Operation> = serde_json::from_value(json!([
+            {"op": "test", "path": "/a~1b", "value": 1},
+            {"op": "add", "path": "/foo/1", "value": "qux"},
+            {"op": "add", "path": "/foo/-", "value": "end"},
+            {"op": "remove", "path": "/qux/m~0n"},
+            {"op": "replace", "path": "/a~1b", "value": 3},
+            {"op": "copy", "from": "/foo/0", "path": "/copied"},
+            {"op": "move", "from": "/copied", "path": "/qux/moved"},
+        ]))
+        .unwrap();
+        Patch::from(operations).apply(&mut target).unwrap();
+        assert_eq!(
+            target,
+            json!({
+                "foo": ["bar", "qux", "baz", "end"],
+                "a/b": 3,
+                "qux": {"moved": "bar"},
+            })
+        );
+    }
+
+    #[test]
+    fn json_errors() {
+        let original = json!({"foo": [1], "bar": {"baz": true}});
+        let cases = [
+            (
+                json!({"op": "test", "path": "/foo/0", "value": 2}),
+                OperationError::TestFailed("/foo/0".to_string()),
+            ),
+            (
+                json!({"op": "remove", "path": "/nope"}),
+                OperationError::NotFound("/nope".to_string()),
+            ),
+            (
+                json!({"op": "replace", "path": "/foo/1", "value": 2}),
+                OperationError::NotFound("/foo/1".to_string()),
+            ),
+            (
+                json!({"op": "add", "path": "/foo/01", "value": 2}),
+                OperationError::InvalidPath("/foo/01".to_string()),
+            ),
+            (
+                json!({"op": "add", "path": "foo", "value": 2}),
+                OperationError::InvalidPath("foo".to_string()),
+            ),
+            (
+                json!({"op": "move", "from": "/bar", "path": "/bar/baz"}),
+                OperationError::MoveIntoChild {
+                    from: "/bar".to_string(),
+                    path: "/bar/baz".to_string(),
+                },
+            ),
+        ];
+        for (operation, expected) in cases {
+            let mut target = original.clone();
+            let patch = Patch::Json(vec![
+                serde_json::from_value(json!({"op": "add", "path": "/new", "value": 1})).unwrap(),
+                serde_json::from_value(operation).unwrap(),
+            ]);
+            let err = patch.apply(&mut target).unwrap_err();
+            assert!(
+                matches!(&err, Error::Patch { index: 1, source } if *source == expected),
+                "{err:?}"
+            );
+            assert_eq!(target, original);
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! JSON Schemas for the [filter extension](https://github.com/stac-api-extensions/filter)'s
+//! `/queryables` endpoints.
+//!
+//! **pgstac** registers queryables in its `queryables` table, each with an
+//! optional JSON Schema definition. [schema] merges them with the core STAC
+//! fields that can always be filtered on into a document that's ready to
+//! serve, and [Client::queryables](crate::Client::queryables) does the same
+//! from a database.
+
+use serde_json::{json, Map, Value};
+
+/// The JSON Schema dialect of queryables documents.
+pub const SCHEMA: &str = "https://json-schema.org/draft/2019-09/schema";
+
+/// Returns a queryables document for these queryables.
+///
+/// `id` is the document's `$id`, usually the url it's served at. Queryables
+/// are name and definition pairs; ones without a definition accept any value,
+/// and ones with the same name as a core field (`id`, `collection`,
+/// `datetime`, or `geometry`) replace its definition.
+///
+/// # Examples
+///
+/// ```
+/// use serde_json::json;
+///
+/// let schema = pgstac::queryables::schema(
+///     Some("https://stac.example.com/queryables"),
+///     [("eo:cloud_cover".to_string(), Some(json!({"type": "number"})))],
+/// );
+/// assert_eq!(schema["properties"]["eo:cloud_cover"]["type"], "number");
+/// assert!(schema["properties"]["datetime"].is_object());
+/// ```
+pub fn schema(
+    id: Option<&str>,
+    queryables: impl IntoIterator<Item = (String, Option<Value>)>,
+) -> Value {
+    let mut properties = core();
+    for (name, definition) in queryables {
+        let definition = definition
+            .filter(Value::is_object)
+            .unwrap_or_else(|| json!({}));
+        let _ = properties.insert(name, definition);
+    }
+    let mut schema = json!({
+        "$schema": SCHEMA,
+        "type": "object",
+        "title": "Queryables",
+        "properties": properties,
+        "additionalProperties": true,
+    });
+    if let Some(id) = id {
+        schema["$id"] = id.into();
+    }
+    schema
+}
+
+fn core() -> Map<String, Value> {
+    let item = "https://schemas.stacspec.org/v1.0.0/item-spec/json-schema/item.json";
+    [
+        (
+            "id",
+            json!({
+                "title": "Item ID",
+                "description": "Item identifier",
+                "$ref": format!("{}#/definitions/core/allOf/2/properties/id", item),
+            }),
+        ),
+        (
+            "collection",
+            json!({
+                "title": "Collection",
+                "description": "The id of the collection the item belongs to",
+                "type": "string",
+            }),
+        ),
+        (
+            "datetime",
+            json!({
+                "title": "Datetime",
+                "description": "Datetime",
+                "type": "string",
+                "format": "date-time",
+            }),
+        ),
+        (
+            "geometry",
+            json!({
+                "title": "Item Geometry",
+                "description": "Item Geometry",
+                "$ref": "https://geojson.org/schema/Feature.json",
+            }),
+        ),
+    ]
+    .into_iter()
+    .map(|(name, definition)| (name.to_string(), definition))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    #[test]
+    fn schema() {
+        let schema = super::schema(
+            None,
+            [
+                (
+                    "eo:cloud_cover".to_string(),
+                    Some(json!({"type": "number"})),
+                ),
+                ("platform".to_string(), None),
+                ("datetime".to_string(), Some(json!({"type": "string"}))),
+            ],
+        );
+        assert!(schema.get("$id").is_none());
+        assert_eq!(schema["$schema"], super::SCHEMA);
+        let properties = schema["properties"].as_object().unwrap();
+        assert_eq!(properties.len(), 6);
+        assert_eq!(properties["platform"], json!({}));
+        assert_eq!(properties["datetime"], json!({"type": "string"}));
+        assert_eq!(properties["geometry"]["title"], "Item Geometry");
+    }
+}
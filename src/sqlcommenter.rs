@@ -0,0 +1,144 @@
+//! [sqlcommenter](https://google.github.io/sqlcommenter/)-style comments on
+//! the SQL that a [Client](crate::Client) runs.
+//!
+//! A [SqlCommenter], set with
+//! [Client::sql_commenter](crate::Client::sql_commenter), appends a comment
+//! with the application's name and the current `traceparent` to every
+//! statement, so queries in `pg_stat_statements` or the server log can be
+//! matched with the traces that ran them.
+
+use std::{fmt::Debug, sync::Arc};
+
+/// Builds the comment that's appended to each statement.
+#[derive(Clone, Default)]
+pub struct SqlCommenter {
+    application: Option<String>,
+    traceparent: Option<Arc<dyn Fn() -> Option<String> + Send + Sync>>,
+}
+
+impl SqlCommenter {
+    /// Creates a commenter that doesn't add anything yet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pgstac::sqlcommenter::SqlCommenter;
+    ///
+    /// let commenter = SqlCommenter::new();
+    /// assert_eq!(commenter.comment(), None);
+    /// ```
+    pub fn new() -> SqlCommenter {
+        SqlCommenter::default()
+    }
+
+    /// Sets the application name, added as the `application` tag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pgstac::sqlcommenter::SqlCommenter;
+    ///
+    /// let commenter = SqlCommenter::new().application("stac-server");
+    /// assert_eq!(commenter.comment().unwrap(), "/*application='stac-server'*/");
+    /// ```
+    pub fn application(mut self, application: impl ToString) -> SqlCommenter {
+        self.application = Some(application.to_string());
+        self
+    }
+
+    /// Sets a function that returns the current W3C `traceparent`, if any.
+    ///
+    /// The function is called for every statement, so it should be cheap,
+    /// e.g. reading the current OpenTelemetry context.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pgstac::sqlcommenter::SqlCommenter;
+    ///
+    /// let commenter = SqlCommenter::new().traceparent(|| {
+    ///     Some("00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01".to_string())
+    /// });
+    /// assert_eq!(
+    ///     commenter.comment().unwrap(),
+    ///     "/*traceparent='00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01'*/"
+    /// );
+    /// ```
+    pub fn traceparent(
+        mut self,
+        traceparent: impl Fn() -> Option<String> + Send + Sync + 'static,
+    ) -> SqlCommenter {
+        self.traceparent = Some(Arc::new(traceparent));
+        self
+    }
+
+    /// Returns the comment for a statement that's about to run, or `None` if there are no tags.
+    ///
+    /// Tags are sorted by key, and keys and values are percent-encoded, as
+    /// sqlcommenter specifies.
+    pub fn comment(&self) -> Option<String> {
+        let mut tags = Vec::with_capacity(2);
+        if let Some(application) = &self.application {
+            tags.push(("application", application.clone()));
+        }
+        if let Some(traceparent) = self.traceparent.as_ref().and_then(|f| f()) {
+            tags.push(("traceparent", traceparent));
+        }
+        if tags.is_empty() {
+            return None;
+        }
+        tags.sort();
+        let tags = tags
+            .into_iter()
+            .map(|(key, value)| format!("{}='{}'", encode(key), encode(&value)))
+            .collect::<Vec<_>>();
+        Some(format!("/*{}*/", tags.join(",")))
+    }
+
+    /// Appends the comment, if any, to a statement.
+    pub(crate) fn apply(&self, sql: &str) -> Option<String> {
+        self.comment()
+            .map(|comment| format!("{} {}", sql.trim_end().trim_end_matches(';'), comment))
+    }
+}
+
+impl Debug for SqlCommenter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SqlCommenter")
+            .field("application", &self.application)
+            .field("traceparent", &self.traceparent.is_some())
+            .finish()
+    }
+}
+
+/// Percent-encodes everything but unreserved characters, so a value can't
+/// close the comment or its quotes.
+fn encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SqlCommenter;
+
+    #[test]
+    fn apply() {
+        let commenter = SqlCommenter::new()
+            .application("it's */ here")
+            .traceparent(|| Some("00-abc-def-01".to_string()));
+        assert_eq!(
+            commenter.apply("SELECT 1;").unwrap(),
+            "SELECT 1 /*application='it%27s%20%2A%2F%20here',traceparent='00-abc-def-01'*/"
+        );
+        assert!(SqlCommenter::new().apply("SELECT 1").is_none());
+    }
+}
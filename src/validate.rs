@@ -0,0 +1,396 @@
+//! Client-side validation of items before they're written.
+//!
+//! **pgstac** does very little checking of what it's given, and what it does
+//! check fails with database errors that don't say much about what's wrong.
+//! A [Validate] implementation, set with
+//! [Client::validator](crate::Client::validator) or
+//! [Loader::validator](crate::loader::Loader::validator), rejects invalid
+//! items before they're sent, with a JSON pointer to each problem.
+//!
+//! With the `validate` feature, [SchemaValidator] validates against a JSON
+//! schema, e.g. the STAC item schema.
+//!
+//! [ValidateSearch] checks searches the same way, catching the mistakes that
+//! **pgstac** would otherwise fail on, or silently return nothing for.
+
+use crate::{Error, Result};
+use serde_json::Value;
+use stac_api::Search;
+use std::{fmt::Debug, sync::Arc};
+
+/// Validates items.
+///
+/// Validation is run on a blocking thread, so implementations are free to
+/// block, e.g. to fetch schemas.
+pub trait Validate: Debug + Send + Sync {
+    /// Validates an item, returning everything that's wrong with it.
+    fn validate(&self, item: &Value) -> Vec<Violation>;
+}
+
+/// Something wrong with an item.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Violation {
+    /// A JSON pointer to the invalid part of the item.
+    pub pointer: String,
+
+    /// What's wrong.
+    pub message: String,
+}
+
+/// Validates items against a JSON schema.
+#[cfg(feature = "validate")]
+#[derive(Debug)]
+pub struct SchemaValidator(jsonschema::JSONSchema);
+
+/// The schema for STAC v1.0.0 items.
+pub const STAC_ITEM_SCHEMA: &str =
+    "https://schemas.stacspec.org/v1.0.0/item-spec/json-schema/item.json";
+
+#[cfg(feature = "validate")]
+impl SchemaValidator {
+    /// Creates a new validator from a JSON schema.
+    ///
+    /// Remote `$ref`s are fetched when they're first needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pgstac::validate::SchemaValidator;
+    /// use serde_json::json;
+    ///
+    /// let validator = SchemaValidator::new(&json!({"required": ["id"]})).unwrap();
+    /// ```
+    pub fn new(schema: &Value) -> Result<SchemaValidator> {
+        jsonschema::JSONSchema::compile(schema)
+            .map(SchemaValidator)
+            .map_err(|err| Error::Schema(err.to_string()))
+    }
+
+    /// Creates a validator for STAC v1.0.0 items, fetching the schema.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use pgstac::validate::SchemaValidator;
+    /// # tokio_test::block_on(async {
+    /// let validator = SchemaValidator::stac_item().await.unwrap();
+    /// # });
+    /// ```
+    pub async fn stac_item() -> Result<SchemaValidator> {
+        tokio::task::spawn_blocking(|| {
+            SchemaValidator::new(&serde_json::json!({ "$ref": STAC_ITEM_SCHEMA }))
+        })
+        .await
+        .map_err(|err| Error::Boxed(Box::new(err)))?
+    }
+}
+
+#[cfg(feature = "validate")]
+impl Validate for SchemaValidator {
+    fn validate(&self, item: &Value) -> Vec<Violation> {
+        match self.0.validate(item) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors
+                .map(|error| Violation {
+                    pointer: error.instance_path.to_string(),
+                    message: error.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Pre-flight validation of searches.
+pub trait ValidateSearch {
+    /// Validates a search, returning everything that's wrong with it.
+    ///
+    /// Unlike [Search::validate], which stops at the first problem, this
+    /// checks that:
+    ///
+    /// - `bbox` has four or six numbers, with south before north and (for
+    ///   six) bottom before top. West can be greater than east for boxes that
+    ///   cross the antimeridian.
+    /// - `datetime` is an RFC 3339 datetime or interval, and an interval's
+    ///   start isn't after its end.
+    /// - `sortby` directions are `asc` or `desc`, and fields aren't empty.
+    /// - no field is both included and excluded by `fields`.
+    /// - `bbox` and `intersects` aren't both set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pgstac::validate::ValidateSearch;
+    /// use stac_api::Search;
+    ///
+    /// let search = Search {
+    ///     bbox: Some(vec![-105.0, 41.0, -106.0]),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(search.violations()[0].pointer, "/bbox");
+    /// ```
+    fn violations(&self) -> Vec<Violation>;
+}
+
+impl ValidateSearch for Search {
+    fn violations(&self) -> Vec<Violation> {
+        match serde_json::to_value(self) {
+            Ok(value) => value.violations(),
+            Err(err) => vec![violation("", err.to_string())],
+        }
+    }
+}
+
+/// Validates a search's JSON, e.g. a request body before it's deserialized.
+impl ValidateSearch for Value {
+    fn violations(&self) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        if let Some(bbox) = self.get("bbox").filter(|bbox| !bbox.is_null()) {
+            validate_bbox(bbox, &mut violations);
+        }
+        if self.get("intersects").is_some_and(|i| !i.is_null())
+            && self.get("bbox").is_some_and(|b| !b.is_null())
+        {
+            violations.push(violation(
+                "/intersects",
+                "bbox and intersects can't both be set",
+            ));
+        }
+        match self.get("datetime") {
+            None | Some(Value::Null) => {}
+            Some(Value::String(datetime)) => match stac::datetime::parse(datetime) {
+                Ok((Some(start), Some(end))) if start > end => {
+                    violations.push(violation("/datetime", "start is after end"))
+                }
+                Ok(_) => {}
+                Err(err) => violations.push(violation("/datetime", err.to_string())),
+            },
+            Some(_) => violations.push(violation("/datetime", "must be a string")),
+        }
+        if let Some(sortby) = self.get("sortby").and_then(Value::as_array) {
+            for (i, sortby) in sortby.iter().enumerate() {
+                if sortby
+                    .get("field")
+                    .and_then(Value::as_str)
+                    .is_none_or(str::is_empty)
+                {
+                    violations.push(violation(
+                        format!("/sortby/{}/field", i),
+                        "field is required",
+                    ));
+                }
+                match sortby.get("direction") {
+                    None => {}
+                    Some(Value::String(direction)) if direction == "asc" || direction == "desc" => {
+                    }
+                    Some(direction) => violations.push(violation(
+                        format!("/sortby/{}/direction", i),
+                        format!("unknown direction: {}", direction),
+                    )),
+                }
+            }
+        }
+        if let Some(fields) = self.get("fields") {
+            let names = |key| {
+                fields
+                    .get(key)
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>()
+            };
+            let exclude = names("exclude");
+            for field in names("include") {
+                if exclude.contains(&field) {
+                    violations.push(violation(
+                        "/fields",
+                        format!("{} is both included and excluded", field),
+                    ));
+                }
+            }
+        }
+        violations
+    }
+}
+
+fn validate_bbox(bbox: &Value, violations: &mut Vec<Violation>) {
+    let Some(bbox) = bbox
+        .as_array()
+        .and_then(|bbox| bbox.iter().map(Value::as_f64).collect::<Option<Vec<_>>>())
+    else {
+        violations.push(violation("/bbox", "must be an array of numbers"));
+        return;
+    };
+    let (south, north, bottom_top) = match bbox.as_slice() {
+        [_, south, _, north] => (*south, *north, None),
+        [_, south, bottom, _, north, top] => (*south, *north, Some((*bottom, *top))),
+        _ => {
+            violations.push(violation(
+                "/bbox",
+                format!("must have 4 or 6 numbers, not {}", bbox.len()),
+            ));
+            return;
+        }
+    };
+    if south > north {
+        violations.push(violation("/bbox", "south is greater than north"));
+    }
+    if let Some((bottom, top)) = bottom_top {
+        if bottom > top {
+            violations.push(violation("/bbox", "bottom is greater than top"));
+        }
+    }
+    if bbox.iter().any(|value| !value.is_finite()) {
+        violations.push(violation("/bbox", "must be finite"));
+    }
+}
+
+fn violation(pointer: impl ToString, message: impl ToString) -> Violation {
+    Violation {
+        pointer: pointer.to_string(),
+        message: message.to_string(),
+    }
+}
+
+/// Serializes and validates items on a blocking thread.
+///
+/// Returns [Error::Invalid] for the first invalid item.
+pub(crate) async fn validate<T>(
+    validator: Option<&Arc<dyn Validate>>,
+    items: Vec<T>,
+) -> Result<Vec<Value>>
+where
+    T: serde::Serialize,
+{
+    let values = items
+        .into_iter()
+        .map(serde_json::to_value)
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let Some(validator) = validator.cloned() else {
+        return Ok(values);
+    };
+    tokio::task::spawn_blocking(move || {
+        for value in &values {
+            let violations = validator.validate(value);
+            if !violations.is_empty() {
+                return Err(Error::Invalid {
+                    id: value
+                        .get("id")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string(),
+                    violations,
+                });
+            }
+        }
+        Ok(values)
+    })
+    .await
+    .map_err(|err| Error::Boxed(Box::new(err)))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Validate, ValidateSearch, Violation};
+    use crate::Error;
+    use serde_json::{json, Value};
+    use stac_api::{Fields, Search, Sortby};
+    use std::sync::Arc;
+
+    #[derive(Debug)]
+    struct RequireGeometry;
+
+    impl Validate for RequireGeometry {
+        fn validate(&self, item: &Value) -> Vec<Violation> {
+            if item["geometry"].is_null() {
+                vec![Violation {
+                    pointer: "/geometry".to_string(),
+                    message: "geometry is required".to_string(),
+                }]
+            } else {
+                Vec::new()
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn validate() {
+        let validator: Arc<dyn Validate> = Arc::new(RequireGeometry);
+        let items = vec![
+            json!({"id": "a", "geometry": {"type": "Point", "coordinates": [0, 0]}}),
+            json!({"id": "b", "geometry": null}),
+        ];
+        assert_eq!(super::validate(None, items.clone()).await.unwrap().len(), 2);
+        let err = super::validate(Some(&validator), items).await.unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Invalid { id, violations } if id == "b" && violations[0].pointer == "/geometry"
+        ));
+    }
+
+    #[cfg(feature = "validate")]
+    #[test]
+    fn schema_validator() {
+        let validator =
+            super::SchemaValidator::new(&json!({"properties": {"id": {"type": "string"}}}))
+                .unwrap();
+        assert!(validator.validate(&json!({"id": "a"})).is_empty());
+        let violations = validator.validate(&json!({"id": 42}));
+        assert_eq!(violations[0].pointer, "/id");
+    }
+
+    #[test]
+    fn validate_search() {
+        assert!(Search::default().violations().is_empty());
+        let search = Search {
+            bbox: Some(vec![170.0, 40.0, -170.0, 41.0]),
+            datetime: Some("2023-01-01T00:00:00Z/..".to_string()),
+            sortby: Some(vec![Sortby::desc("datetime")]),
+            ..Default::default()
+        };
+        assert!(search.violations().is_empty());
+
+        let search = Search {
+            bbox: Some(vec![-106.0, 41.0, -105.0, 40.0]),
+            datetime: Some("2023-02-01T00:00:00Z/2023-01-01T00:00:00Z".to_string()),
+            fields: Some(Fields {
+                include: vec!["id".to_string()],
+                exclude: vec!["id".to_string()],
+            }),
+            intersects: Some(
+                serde_json::from_value(json!({"type": "Point", "coordinates": [-105.5, 40.5]}))
+                    .unwrap(),
+            ),
+            ..Default::default()
+        };
+        let pointers = search
+            .violations()
+            .into_iter()
+            .map(|violation| violation.pointer)
+            .collect::<Vec<_>>();
+        assert_eq!(pointers, ["/bbox", "/intersects", "/datetime", "/fields"]);
+    }
+
+    #[test]
+    fn validate_search_value() {
+        let violations = json!({
+            "bbox": [1, 2, 3],
+            "datetime": "yesterday",
+            "sortby": [{"field": "", "direction": "up"}]
+        })
+        .violations();
+        let pointers = violations
+            .iter()
+            .map(|violation| violation.pointer.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            pointers,
+            [
+                "/bbox",
+                "/datetime",
+                "/sortby/0/field",
+                "/sortby/0/direction"
+            ]
+        );
+    }
+}